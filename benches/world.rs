@@ -0,0 +1,120 @@
+// benches/world.rs
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hylaean_path::constellation::{spawn_random_constellation, SpawnConfig};
+use hylaean_path::ecs::{gravity_system, propagate_system, Position, Velocity, World};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+const MU: f64 = 3.986004418e14;
+const N_ENTITIES: usize = 10_000;
+
+fn populated_world() -> World {
+    let mut world = World::new();
+    let config = SpawnConfig {
+        radius_range: 6.5e6..7.0e6,
+        eccentricity_range: 0.0..0.1,
+        mu: MU,
+    };
+    spawn_random_constellation(&mut world, N_ENTITIES, &mut StdRng::seed_from_u64(42), &config);
+    world
+}
+
+fn step(c: &mut Criterion) {
+    let mut world = populated_world();
+    c.bench_function("gravity_and_propagate_10k", |b| {
+        b.iter(|| {
+            gravity_system(&mut world, black_box(10.0), MU);
+            propagate_system(&mut world, black_box(10.0));
+        })
+    });
+}
+
+/// The layout `World` used before it moved to dense vectors: one hash map per component,
+/// keyed by entity id.
+struct HashMapWorld {
+    positions: HashMap<usize, Position>,
+    velocities: HashMap<usize, Velocity>,
+}
+
+impl HashMapWorld {
+    fn from_world(world: &World) -> Self {
+        Self {
+            positions: world.iter_mobile().map(|(id, p, _)| (id, p.clone())).collect(),
+            velocities: world.iter_mobile().map(|(id, _, v)| (id, v.clone())).collect(),
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        for (id, vel) in self.velocities.iter_mut() {
+            let pos = &self.positions[id];
+            let accel = central_acceleration(pos);
+            vel.dx += accel[0] * dt;
+            vel.dy += accel[1] * dt;
+            vel.dz += accel[2] * dt;
+        }
+        for (id, pos) in self.positions.iter_mut() {
+            let vel = &self.velocities[id];
+            pos.x += vel.dx * dt;
+            pos.y += vel.dy * dt;
+            pos.z += vel.dz * dt;
+        }
+    }
+}
+
+fn central_acceleration(pos: &Position) -> [f64; 3] {
+    let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+    let k = -MU / (r * r * r);
+    [k * pos.x, k * pos.y, k * pos.z]
+}
+
+/// Same serial gravity + propagate step as [`HashMapWorld::step`], over the dense vectors.
+fn dense_step(world: &mut World, dt: f64) {
+    for (pos, vel) in world.positions.iter().zip(world.velocities.iter_mut()) {
+        let accel = central_acceleration(pos);
+        vel.dx += accel[0] * dt;
+        vel.dy += accel[1] * dt;
+        vel.dz += accel[2] * dt;
+    }
+    for (pos, vel) in world.positions.iter_mut().zip(world.velocities.iter()) {
+        pos.x += vel.dx * dt;
+        pos.y += vel.dy * dt;
+        pos.z += vel.dz * dt;
+    }
+}
+
+/// Old hash-map layout against the dense layout on one thread, so only the memory layout differs.
+fn layout(c: &mut Criterion) {
+    let mut world = populated_world();
+    let mut old = HashMapWorld::from_world(&world);
+    let mut group = c.benchmark_group("layout_step_10k");
+    group.bench_function("hashmap", |b| b.iter(|| old.step(black_box(10.0))));
+    group.bench_function("dense", |b| b.iter(|| dense_step(&mut world, black_box(10.0))));
+    group.finish();
+}
+
+fn churn(c: &mut Criterion) {
+    let mut world = populated_world();
+    c.bench_function("remove_and_readd_1k_of_10k", |b| {
+        b.iter(|| {
+            // Remove every tenth entity, then put the same states back under the freed ids.
+            let ids: Vec<_> = world.entities.iter().step_by(10).copied().collect();
+            let removed: Vec<_> = ids
+                .into_iter()
+                .map(|id| {
+                    let index = world.index_of(id).unwrap();
+                    let state = (world.positions[index].clone(), world.velocities[index].clone());
+                    world.remove_entity(id);
+                    state
+                })
+                .collect();
+            for (pos, vel) in removed {
+                world.add_entity(pos, vel);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, step, layout, churn);
+criterion_main!(benches);
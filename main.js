@@ -90,13 +90,14 @@ async function run() {
       console.log(proximityWarnings);
       // Update satellite positions and colors.
       const positions = sim.get_positions(); // Returns an array of [x, y, z] arrays.
+      const ids = sim.get_ids(); // Id of the satellite behind each entry of `positions`.
       positions.forEach((pos, i) => {
         if (satelliteMeshes[i]) {
           // Update position
           satelliteMeshes[i].position.set(pos[0], pos[1], pos[2]);
           
           // Update color based on proximity warning
-          if (proximityWarnings.includes(i)) {
+          if (proximityWarnings.includes(ids[i])) {
             // Set to bright red for satellites in proximity
             satelliteMeshes[i].material.color.setRGB(1, 0, 0);
           } else {
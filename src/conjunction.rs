@@ -0,0 +1,190 @@
+// src/conjunction.rs
+
+//! Conjunction screening between pairs of satellites.
+
+use crate::ecs::{proximity_detection_grid, EntityId, Position, Velocity, World};
+use crate::orbit::propagate_kepler;
+use std::collections::HashSet;
+
+/// Time (s, relative to now) at which two satellites are closest, assuming linear relative motion.
+///
+/// Minimizes |Δr + Δv t|², giving t = -(Δr · Δv) / |Δv|². Returns 0.0 if the satellites are
+/// already separating or have no relative velocity.
+pub fn time_of_closest_approach(p1: &Position, v1: &Velocity, p2: &Position, v2: &Velocity) -> f64 {
+    let dr = [p2.x - p1.x, p2.y - p1.y, p2.z - p1.z];
+    let dv = [v2.dx - v1.dx, v2.dy - v1.dy, v2.dz - v1.dz];
+    let dv2 = dv[0] * dv[0] + dv[1] * dv[1] + dv[2] * dv[2];
+    if dv2 == 0.0 {
+        return 0.0;
+    }
+    let t = -(dr[0] * dv[0] + dr[1] * dv[1] + dr[2] * dv[2]) / dv2;
+    t.max(0.0)
+}
+
+/// Distance (m) between two satellites at their [`time_of_closest_approach`].
+pub fn closest_approach_distance(p1: &Position, v1: &Velocity, p2: &Position, v2: &Velocity) -> f64 {
+    let t = time_of_closest_approach(p1, v1, p2, v2);
+    let dx = (p2.x - p1.x) + (v2.dx - v1.dx) * t;
+    let dy = (p2.y - p1.y) + (v2.dy - v1.dy) * t;
+    let dz = (p2.z - p1.z) + (v2.dz - v1.dz) * t;
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// Finds the pairs that come closer than `threshold` within the next `horizon` seconds, returning
+/// `(a, b, t)` with `a < b` and `t` the first sampled time (s from now) at which they breach it,
+/// ordered by `t`, then `a`, then `b`.
+///
+/// Every entity is propagated analytically on its two-body orbit about `mu` with
+/// [`propagate_kepler`] to the times 0, `step`, 2 `step`, ... up to `horizon`, so there is no
+/// accumulated integration error. Pairs that close and separate again between two samples are
+/// missed, so `step` should be short compared with `threshold` divided by the typical relative
+/// speed. The cost grows with `horizon / step`. Returns nothing if `step` is not a finite positive
+/// number, `horizon` is not finite and non-negative, or the sample count overflows `usize`.
+pub fn scan_conjunctions(world: &World, threshold: f64, horizon: f64, step: f64, mu: f64) -> Vec<(EntityId, EntityId, f64)> {
+    if !step.is_finite() || step <= 0.0 || !horizon.is_finite() || horizon < 0.0 {
+        return Vec::new();
+    }
+    let Some(samples) = ((horizon / step).floor() as usize).checked_add(1) else {
+        return Vec::new();
+    };
+    // A copy with the same ids and storage order, whose positions are overwritten each sample, so
+    // warnings on it name the entities of `world` directly.
+    let mut future = World::new();
+    future.restore(&world.snapshot());
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+    for k in 0..samples {
+        let t = k as f64 * step;
+        for ((slot, pos), vel) in future.positions.iter_mut().zip(&world.positions).zip(&world.velocities) {
+            *slot = propagate_kepler(pos, vel, t, mu).0;
+        }
+        for warning in proximity_detection_grid(&future, threshold) {
+            if seen.insert((warning.a, warning.b)) {
+                found.push((warning.a, warning.b, t));
+            }
+        }
+    }
+    found.sort_by(|x, y| x.2.total_cmp(&y.2).then((x.0, x.1).cmp(&(y.0, y.1))));
+    found
+}
+
+/// Radial and angular sample counts for the probability integral in [`collision_probability`].
+const PC_RADIAL_STEPS: usize = 64;
+const PC_ANGULAR_STEPS: usize = 128;
+
+/// Probability that two objects collide at closest approach (Foster's 2D method).
+///
+/// `rel_pos` and `rel_cov` are the miss vector and its combined covariance (m, m²) in the
+/// encounter frame: x and y span the encounter plane and z lies along the relative velocity.
+/// The z components are ignored, and the 2D Gaussian of the in-plane miss is integrated over the
+/// disc of radius `combined_radius` around the origin. Returns NaN if the in-plane covariance is
+/// not positive definite.
+pub fn collision_probability(rel_pos: [f64; 3], rel_cov: [[f64; 3]; 3], combined_radius: f64) -> f64 {
+    let (sxx, sxy, syy) = (rel_cov[0][0], 0.5 * (rel_cov[0][1] + rel_cov[1][0]), rel_cov[1][1]);
+    let det = sxx * syy - sxy * sxy;
+    if sxx <= 0.0 || det <= 0.0 {
+        return f64::NAN;
+    }
+    if combined_radius <= 0.0 {
+        return 0.0;
+    }
+    let (ixx, ixy, iyy) = (syy / det, -sxy / det, sxx / det);
+    let norm = 1.0 / (std::f64::consts::TAU * det.sqrt());
+
+    // Midpoint rule in polar coordinates over the hard-body disc.
+    let dr = combined_radius / PC_RADIAL_STEPS as f64;
+    let dtheta = std::f64::consts::TAU / PC_ANGULAR_STEPS as f64;
+    let mut sum = 0.0;
+    for i in 0..PC_RADIAL_STEPS {
+        let r = (i as f64 + 0.5) * dr;
+        for j in 0..PC_ANGULAR_STEPS {
+            let (sin, cos) = ((j as f64 + 0.5) * dtheta).sin_cos();
+            let x = r * cos - rel_pos[0];
+            let y = r * sin - rel_pos[1];
+            let q = ixx * x * x + 2.0 * ixy * x * y + iyy * y * y;
+            sum += (-0.5 * q).exp() * r;
+        }
+    }
+    (norm * sum * dr * dtheta).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_approach_of_crossing_trajectories() {
+        let (p1, v1) = (Position { x: -1000.0, y: 0.0, z: 0.0 }, Velocity { dx: 10.0, dy: 0.0, dz: 0.0 });
+        let (p2, v2) = (Position { x: 0.0, y: -2000.0, z: 0.0 }, Velocity { dx: 0.0, dy: 10.0, dz: 0.0 });
+
+        // Relative motion (1000, -2000) + (-10, 10) t is closest at t = 150 s, at (-500, -500).
+        assert!((time_of_closest_approach(&p1, &v1, &p2, &v2) - 150.0).abs() < 1e-9);
+        assert!((closest_approach_distance(&p1, &v1, &p2, &v2) - 500.0 * 2f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn separating_satellites_are_closest_now() {
+        let (p1, v1) = (Position { x: 0.0, y: 0.0, z: 0.0 }, Velocity { dx: -1.0, dy: 0.0, dz: 0.0 });
+        let (p2, v2) = (Position { x: 30.0, y: 40.0, z: 0.0 }, Velocity { dx: 1.0, dy: 0.0, dz: 0.0 });
+
+        assert_eq!(time_of_closest_approach(&p1, &v1, &p2, &v2), 0.0);
+        assert_eq!(closest_approach_distance(&p1, &v1, &p2, &v2), 50.0);
+    }
+
+    #[test]
+    fn collision_probability_head_on_and_far_miss() {
+        let cov = [[100.0, 0.0, 0.0], [0.0, 100.0, 0.0], [0.0, 0.0, 1.0e6]];
+
+        // Centered on an isotropic Gaussian the disc holds 1 - exp(-R² / 2σ²) = 1 - e^(-1/2).
+        let head_on = collision_probability([0.0, 0.0, 0.0], cov, 10.0);
+        assert!((head_on - (1.0 - (-0.5f64).exp())).abs() < 1e-3, "head-on {head_on}");
+
+        // A miss of 100σ is astronomically unlikely to hit.
+        let far = collision_probability([1000.0, 0.0, 0.0], cov, 10.0);
+        assert!(far < 1e-100, "far miss {far}");
+
+        assert!(collision_probability([0.0; 3], [[0.0; 3]; 3], 10.0).is_nan());
+    }
+
+    #[test]
+    fn scan_finds_future_conjunction_of_crossing_orbits() {
+        const MU: f64 = 3.986004418e14;
+        let r = 7.0e6;
+        let v = (MU / r).sqrt();
+        // An equatorial and a polar circular orbit, each a quarter period away from the node at
+        // (r, 0, 0), which both reach at the same time.
+        let mut world = World::new();
+        world.add_entity(Position { x: 0.0, y: -r, z: 0.0 }, Velocity { dx: v, dy: 0.0, dz: 0.0 });
+        world.add_entity(Position { x: 0.0, y: 0.0, z: -r }, Velocity { dx: v, dy: 0.0, dz: 0.0 });
+        let quarter = crate::orbit::orbital_period(r, MU) / 4.0;
+
+        let found = scan_conjunctions(&world, 1.0e4, 2.0 * quarter, 1.0, MU);
+
+        assert_eq!(found.len(), 1);
+        let (a, b, t) = found[0];
+        assert_eq!((a, b), (0, 1));
+        assert!(t > quarter - 2.0 && t <= quarter, "t = {t}, node at {quarter}");
+        assert!(scan_conjunctions(&world, 1.0e4, quarter - 2.0, 1.0, MU).is_empty());
+    }
+
+    #[test]
+    fn scan_reports_source_ids_and_rejects_unbounded_sampling() {
+        const MU: f64 = 3.986004418e14;
+        let r = 7.0e6;
+        let v = (MU / r).sqrt();
+        let mut world = World::new();
+        let gone = world.add_entity(Position { x: -r, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: -v, dz: 0.0 });
+        let a = world.add_entity(Position { x: 0.0, y: -r, z: 0.0 }, Velocity { dx: v, dy: 0.0, dz: 0.0 });
+        let b = world.add_entity(Position { x: 0.0, y: 0.0, z: -r }, Velocity { dx: v, dy: 0.0, dz: 0.0 });
+        // Moves `b` into storage slot 0, so storage order no longer follows the ids.
+        world.remove_entity(gone);
+        let quarter = crate::orbit::orbital_period(r, MU) / 4.0;
+
+        let found = scan_conjunctions(&world, 1.0e4, 2.0 * quarter, 1.0, MU);
+        assert_eq!(found.iter().map(|&(a, b, _)| (a, b)).collect::<Vec<_>>(), vec![(a, b)]);
+
+        for (horizon, step) in [(f64::INFINITY, 1.0), (1.0e3, f64::NAN), (1.0e3, f64::INFINITY), (f64::MAX, f64::MIN_POSITIVE)] {
+            assert!(scan_conjunctions(&world, 1.0e4, horizon, step, MU).is_empty());
+        }
+    }
+}
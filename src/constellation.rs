@@ -0,0 +1,367 @@
+// src/constellation.rs
+
+//! Generators that populate a [`World`] with satellites, and summaries of the result.
+
+use crate::ecs::{EntityId, Position, Velocity, World, EARTH_EQUATORIAL_RADIUS};
+use crate::orbit::{elements_to_state, state_to_elements, OrbitalElements};
+use rand::Rng;
+use std::f64::consts::TAU;
+use std::ops::Range;
+
+/// Parameters for [`spawn_random_constellation`].
+#[derive(Debug, Clone)]
+pub struct SpawnConfig {
+    /// Range of initial orbital radii in meters.
+    pub radius_range: Range<f64>,
+    /// Range of orbital eccentricities.
+    pub eccentricity_range: Range<f64>,
+    /// Gravitational parameter of the central body (m³/s²).
+    pub mu: f64,
+}
+
+/// Adds `n` satellites on random elliptical orbits, each passing through a random point
+/// in full 3D space, drawing every random number from `rng`.
+///
+/// Any generator works, from a seeded `StdRng` for reproducible layouts to a quasi-random
+/// sequence for more even coverage. A generator in the same state with the same `config`
+/// always produces the same satellites.
+///
+/// Panics if either range in `config` is empty.
+pub fn spawn_random_constellation<R: Rng + ?Sized>(world: &mut World, n: usize, rng: &mut R, config: &SpawnConfig) {
+    for _ in 0..n {
+        // Generate a random orbital radius.
+        let r: f64 = rng.gen_range(config.radius_range.clone());
+        // Random azimuth angle (θ) in [0, 2π)
+        let theta = rng.gen_range(0.0..TAU);
+        // Random cosine of inclination (u) in [-1, 1]
+        let u: f64 = rng.gen_range(-1.0..1.0);
+        // Inclination φ = acos(u); sin(φ) = sqrt(1 - u²)
+        let sin_phi = (1.0 - u * u).sqrt();
+
+        // Convert spherical coordinates to Cartesian coordinates.
+        let pos = Position {
+            x: r * sin_phi * theta.cos(),
+            y: r * sin_phi * theta.sin(),
+            z: r * u,
+        };
+
+        // 1. Pick an eccentricity
+        let e: f64 = rng.gen_range(config.eccentricity_range.clone());
+
+        // 2. Pick a random true anomaly ν in [0, 2π)
+        let nu: f64 = rng.gen_range(0.0..TAU);
+
+        // 3. Compute the semi‑latus rectum p = r * (1 + e cos ν)
+        let p = r * (1.0 + e * nu.cos());
+
+        // 4. Compute radial & tangential speeds for an ellipse
+        //    Vᵣ = √(μ/p) · e · sin ν
+        //    Vₜ = √(μ/p) · (1 + e cos ν)
+        let sqrt_mu_p = (config.mu / p).sqrt();
+        let vr = sqrt_mu_p * e * nu.sin();
+        let vt = sqrt_mu_p * (1.0 + e * nu.cos());
+
+        // 5. Unit radial vector r̂ = pos / r
+        let r_hat = (pos.x / r, pos.y / r, pos.z / r);
+
+        // 6. Pick a random “reference” vector and orthogonalize to r̂ to get the orbital plane normal
+        let n_hat = loop {
+            let candidate = (
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            // cross(r̂, candidate)
+            let cross = (
+                r_hat.1 * candidate.2 - r_hat.2 * candidate.1,
+                r_hat.2 * candidate.0 - r_hat.0 * candidate.2,
+                r_hat.0 * candidate.1 - r_hat.1 * candidate.0,
+            );
+            let norm = (cross.0 * cross.0 + cross.1 * cross.1 + cross.2 * cross.2).sqrt();
+            if norm > 1e-6 {
+                break (cross.0 / norm, cross.1 / norm, cross.2 / norm);
+            }
+        };
+
+        // 7. Tangential unit vector θ̂ = cross(n̂, r̂)
+        let theta_hat = (
+            n_hat.1 * r_hat.2 - n_hat.2 * r_hat.1,
+            n_hat.2 * r_hat.0 - n_hat.0 * r_hat.2,
+            n_hat.0 * r_hat.1 - n_hat.1 * r_hat.0,
+        );
+
+        // 8. Combine radial + tangential components
+        let vel = Velocity {
+            dx: vr * r_hat.0 + vt * theta_hat.0,
+            dy: vr * r_hat.1 + vt * theta_hat.1,
+            dz: vr * r_hat.2 + vt * theta_hat.2,
+        };
+
+        world.add_entity(pos, vel);
+    }
+}
+
+/// Adds a Walker-delta constellation i:t/p/f of `total` satellites on circular orbits
+/// `altitude` meters above [`EARTH_EQUATORIAL_RADIUS`], at `inclination` radians.
+///
+/// The satellites are split evenly over `planes` planes whose RAANs are spaced 360°/p apart.
+/// Within a plane they are spaced 360°/(t/p) apart in argument of latitude, and each plane is
+/// shifted by `phasing` × 360°/t relative to the previous one. Plane 0 starts at RAAN 0 and
+/// argument of latitude 0, and satellites are added plane by plane.
+///
+/// Panics if `planes` is zero or does not divide `total`.
+pub fn spawn_walker(
+    world: &mut World,
+    total: usize,
+    planes: usize,
+    phasing: usize,
+    altitude: f64,
+    inclination: f64,
+    mu: f64,
+) {
+    assert!(
+        planes > 0 && total.is_multiple_of(planes),
+        "planes must be non-zero and divide total, got {total} satellites in {planes} planes"
+    );
+    let per_plane = total / planes;
+    for plane in 0..planes {
+        let raan = TAU * plane as f64 / planes as f64;
+        for slot in 0..per_plane {
+            let u = TAU * slot as f64 / per_plane as f64 + TAU * (phasing * plane) as f64 / total as f64;
+            let elements = OrbitalElements {
+                a: EARTH_EQUATORIAL_RADIUS + altitude,
+                e: 0.0,
+                i: inclination,
+                raan,
+                argp: 0.0,
+                nu: u.rem_euclid(TAU),
+            };
+            let (pos, vel) = elements_to_state(&elements, mu);
+            world.add_entity(pos, vel);
+        }
+    }
+}
+
+/// Aggregate geometry of every entity in a world, from [`constellation_stats`].
+#[derive(Debug, Clone)]
+pub struct ConstellationStats {
+    /// Number of entities.
+    pub count: usize,
+    /// Mean distance from the origin in meters.
+    pub mean_radius: f64,
+    /// Smallest distance from the origin in meters.
+    pub min_radius: f64,
+    /// Largest distance from the origin in meters, i.e. the radius of the bounding sphere
+    /// centered on the central body.
+    pub max_radius: f64,
+    /// Mean position of all entities.
+    pub centroid: Position,
+}
+
+/// Summarizes the positions in `world`. With no entities, `count` is 0 and every other field is NaN.
+pub fn constellation_stats(world: &World) -> ConstellationStats {
+    let count = world.positions.len();
+    if count == 0 {
+        return ConstellationStats {
+            count,
+            mean_radius: f64::NAN,
+            min_radius: f64::NAN,
+            max_radius: f64::NAN,
+            centroid: Position { x: f64::NAN, y: f64::NAN, z: f64::NAN },
+        };
+    }
+
+    let mut radius_sum = 0.0;
+    let mut min_radius = f64::INFINITY;
+    let mut max_radius = f64::NEG_INFINITY;
+    let mut sum = [0.0; 3];
+    for pos in &world.positions {
+        let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+        radius_sum += r;
+        min_radius = min_radius.min(r);
+        max_radius = max_radius.max(r);
+        sum[0] += pos.x;
+        sum[1] += pos.y;
+        sum[2] += pos.z;
+    }
+
+    let n = count as f64;
+    ConstellationStats {
+        count,
+        mean_radius: radius_sum / n,
+        min_radius,
+        max_radius,
+        centroid: Position { x: sum[0] / n, y: sum[1] / n, z: sum[2] / n },
+    }
+}
+
+/// Groups the entities that share an orbital plane: inclination and RAAN both within `tol_deg`
+/// degrees of the group's first member.
+///
+/// Entities are visited in ascending id order and each joins the first group it matches, so
+/// the groups are ordered by their smallest id and list their members in ascending order.
+/// RAAN differences wrap around 360°. Entities whose elements are undefined (e.g. at rest)
+/// are left out.
+pub fn group_by_plane(world: &World, mu: f64, tol_deg: f64) -> Vec<Vec<EntityId>> {
+    let tol = tol_deg.to_radians();
+    let mut groups: Vec<(f64, f64, Vec<EntityId>)> = Vec::new();
+    for id in world.entities_sorted() {
+        let i = world.index_of(id).expect("sorted ids are live");
+        let elements = state_to_elements(&world.positions[i], &world.velocities[i], mu);
+        let (inc, raan) = (elements.i, elements.raan);
+        if !inc.is_finite() || !raan.is_finite() {
+            continue;
+        }
+        let matching = groups.iter_mut().find(|(i0, raan0, _)| {
+            let d_raan = (raan - raan0).rem_euclid(TAU);
+            (inc - i0).abs() <= tol && d_raan.min(TAU - d_raan) <= tol
+        });
+        match matching {
+            Some((_, _, members)) => members.push(id),
+            None => groups.push((inc, raan, vec![id])),
+        }
+    }
+    groups.into_iter().map(|(_, _, members)| members).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const MU: f64 = 3.986004418e14;
+
+    fn config() -> SpawnConfig {
+        SpawnConfig { radius_range: 6.5e6..7.0e6, eccentricity_range: 0.0..0.3, mu: MU }
+    }
+
+    /// Every position and velocity component, as bits.
+    fn state_bits(world: &World) -> Vec<u64> {
+        world.iter_mobile()
+            .flat_map(|(_, p, v)| [p.x, p.y, p.z, v.dx, v.dy, v.dz])
+            .map(f64::to_bits)
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_spawns_identical_satellites() {
+        let (mut first, mut second) = (World::new(), World::new());
+        spawn_random_constellation(&mut first, 50, &mut StdRng::seed_from_u64(21), &config());
+        spawn_random_constellation(&mut second, 50, &mut StdRng::seed_from_u64(21), &config());
+
+        assert_eq!(first.positions.len(), 50);
+        assert_eq!(state_bits(&first), state_bits(&second));
+    }
+
+    #[test]
+    fn stats_of_three_satellites() {
+        let mut world = World::new();
+        let still = Velocity { dx: 0.0, dy: 0.0, dz: 0.0 };
+        world.add_entity(Position { x: 7.0e6, y: 0.0, z: 0.0 }, still.clone());
+        world.add_entity(Position { x: 0.0, y: 8.0e6, z: 0.0 }, still.clone());
+        world.add_entity(Position { x: 0.0, y: 0.0, z: -9.0e6 }, still);
+
+        let stats = constellation_stats(&world);
+        assert_eq!(stats.count, 3);
+        assert_eq!((stats.min_radius, stats.max_radius), (7.0e6, 9.0e6));
+        assert!((stats.mean_radius - 8.0e6).abs() < 1e-6);
+        let c = stats.centroid;
+        assert!((c.x - 7.0e6 / 3.0).abs() < 1e-6 && (c.y - 8.0e6 / 3.0).abs() < 1e-6 && (c.z + 3.0e6).abs() < 1e-6);
+
+        let empty = constellation_stats(&World::new());
+        assert_eq!(empty.count, 0);
+        assert!(empty.mean_radius.is_nan());
+    }
+
+    /// Weyl-sequence generator that counts how many 64-bit words it hands out.
+    struct CountingRng {
+        state: u64,
+        calls: usize,
+    }
+
+    impl rand::RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.calls += 1;
+            self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            self.state
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spawns_from_seeded_and_custom_generators() {
+        let radius = |p: &Position| (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+
+        let mut seeded = World::new();
+        spawn_random_constellation(&mut seeded, 20, &mut StdRng::seed_from_u64(90), &config());
+        assert!(seeded.positions.iter().all(|p| config().radius_range.contains(&radius(p))));
+
+        let mut counting = CountingRng { state: 0, calls: 0 };
+        let mut custom = World::new();
+        spawn_random_constellation(&mut custom, 20, &mut counting as &mut dyn rand::RngCore, &config());
+        assert_eq!(custom.positions.len(), 20);
+        assert!(custom.positions.iter().all(|p| config().radius_range.contains(&radius(p))));
+        // Radius, azimuth, cos(inclination), eccentricity, true anomaly and at least one
+        // three-component reference vector per satellite.
+        assert!(counting.calls >= 20 * 8);
+    }
+
+    #[test]
+    fn two_planes_give_two_groups() {
+        let mut world = World::new();
+        spawn_walker(&mut world, 12, 2, 1, 550.0e3, 53f64.to_radians(), MU);
+        // Slightly off plane 0, on the other side of RAAN 0.
+        let (pos, vel) = elements_to_state(
+            &OrbitalElements { a: 7.0e6, e: 0.01, i: 53.2f64.to_radians(), raan: 359.7f64.to_radians(), argp: 0.0, nu: 1.0 },
+            MU,
+        );
+        let stray = world.add_entity(pos, vel);
+
+        let groups = group_by_plane(&world, MU, 0.5);
+
+        let mut first: Vec<EntityId> = (0..6).collect();
+        first.push(stray);
+        assert_eq!(groups, vec![first, (6..12).collect::<Vec<_>>()]);
+        assert_eq!(group_by_plane(&world, MU, 0.1).len(), 3);
+    }
+
+    #[test]
+    fn walker_fills_planes_at_requested_inclination() {
+        let mut world = World::new();
+        let inclination = 53f64.to_radians();
+        spawn_walker(&mut world, 24, 4, 1, 550.0e3, inclination, MU);
+        assert_eq!(world.positions.len(), 24);
+
+        let mut raans: Vec<f64> = Vec::new();
+        for (_, p, v) in world.iter_mobile() {
+            let elements = state_to_elements(p, v, MU);
+            assert!((elements.i - inclination).abs() < 1e-9);
+            assert!((elements.a - (EARTH_EQUATORIAL_RADIUS + 550.0e3)).abs() < 1e-3);
+            // Fold the 0/360° seam onto 0 before comparing.
+            let raan = elements.raan.to_degrees().rem_euclid(360.0);
+            raans.push(if 360.0 - raan < 1e-6 { 0.0 } else { raan });
+        }
+        raans.sort_by(f64::total_cmp);
+        raans.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+        assert_eq!(raans.len(), 4);
+        for (raan, expected) in raans.iter().zip([0.0, 90.0, 180.0, 270.0]) {
+            assert!((raan - expected).abs() < 1e-6);
+        }
+    }
+}
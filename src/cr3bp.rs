@@ -0,0 +1,71 @@
+// src/cr3bp.rs
+
+//! Circular restricted three-body problem in the rotating (synodic) frame.
+//!
+//! Uses normalized units: the distance between the primaries, their total mass and the angular
+//! velocity of the frame are all 1. The larger primary sits at (-μ, 0, 0) and the smaller one
+//! at (1 - μ, 0, 0), where μ is the mass ratio m₂ / (m₁ + m₂). States are `[x, y, z, vx, vy, vz]`.
+
+/// Mass ratio of the Earth-Moon system.
+pub const EARTH_MOON_MASS_RATIO: f64 = 0.012150585609624;
+
+/// Acceleration of a massless particle in the rotating frame, including the Coriolis and
+/// centrifugal terms.
+pub fn cr3bp_acceleration(state: &[f64; 6], mu_ratio: f64) -> [f64; 3] {
+    let [x, y, z, vx, vy, _] = *state;
+    let d1 = [x + mu_ratio, y, z];
+    let d2 = [x - 1.0 + mu_ratio, y, z];
+    let r1 = (d1[0] * d1[0] + d1[1] * d1[1] + d1[2] * d1[2]).sqrt();
+    let r2 = (d2[0] * d2[0] + d2[1] * d2[1] + d2[2] * d2[2]).sqrt();
+    let k1 = (1.0 - mu_ratio) / (r1 * r1 * r1);
+    let k2 = mu_ratio / (r2 * r2 * r2);
+    [
+        2.0 * vy + x - k1 * d1[0] - k2 * d2[0],
+        -2.0 * vx + y - k1 * d1[1] - k2 * d2[1],
+        -k1 * d1[2] - k2 * d2[2],
+    ]
+}
+
+/// Time derivative of a state: velocity followed by acceleration.
+fn derivative(state: &[f64; 6], mu_ratio: f64) -> [f64; 6] {
+    let a = cr3bp_acceleration(state, mu_ratio);
+    [state[3], state[4], state[5], a[0], a[1], a[2]]
+}
+
+/// Returns state + s * d.
+fn add_scaled(state: &[f64; 6], s: f64, d: &[f64; 6]) -> [f64; 6] {
+    std::array::from_fn(|i| state[i] + s * d[i])
+}
+
+/// Advances a state by `dt` (normalized time) with classic RK4.
+pub fn cr3bp_step(state: &mut [f64; 6], dt: f64, mu_ratio: f64) {
+    let k1 = derivative(state, mu_ratio);
+    let k2 = derivative(&add_scaled(state, dt / 2.0, &k1), mu_ratio);
+    let k3 = derivative(&add_scaled(state, dt / 2.0, &k2), mu_ratio);
+    let k4 = derivative(&add_scaled(state, dt, &k3), mu_ratio);
+    for i in 0..6 {
+        state[i] += dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particle_at_l4_stays_there() {
+        let mu = EARTH_MOON_MASS_RATIO;
+        // L4 forms an equilateral triangle with the two primaries.
+        let l4 = [0.5 - mu, 3f64.sqrt() / 2.0, 0.0];
+        let mut state = [l4[0], l4[1], l4[2], 0.0, 0.0, 0.0];
+
+        let a = cr3bp_acceleration(&state, mu);
+        assert!(a.iter().all(|c| c.abs() < 1e-14));
+
+        for _ in 0..1000 {
+            cr3bp_step(&mut state, 0.01, mu);
+        }
+        let drift = ((state[0] - l4[0]).powi(2) + (state[1] - l4[1]).powi(2) + state[2].powi(2)).sqrt();
+        assert!(drift < 1e-9, "drifted {drift}");
+    }
+}
@@ -0,0 +1,53 @@
+// src/cw.rs
+
+//! Clohessy-Wiltshire relative motion about a target on a circular orbit.
+//!
+//! Relative states are `[x, y, z, vx, vy, vz]` in the target's local frame: x radial (away from
+//! the central body), y along-track (direction of motion) and z cross-track (along the orbit
+//! normal).
+
+/// Propagates a relative state by `t` seconds around a target with mean motion `n` (rad/s),
+/// using the closed-form Clohessy-Wiltshire state transition matrix.
+///
+/// A chaser starting at radial offset x₀ with along-track velocity -2 n x₀ stays on a closed
+/// 2:1 ellipse around the target; any other along-track velocity makes it drift.
+pub fn cw_propagate(rel_state: &[f64; 6], n: f64, t: f64) -> [f64; 6] {
+    let [x0, y0, z0, vx0, vy0, vz0] = *rel_state;
+    let nt = n * t;
+    let (s, c) = nt.sin_cos();
+    [
+        (4.0 - 3.0 * c) * x0 + s / n * vx0 + 2.0 / n * (1.0 - c) * vy0,
+        6.0 * (s - nt) * x0 + y0 - 2.0 / n * (1.0 - c) * vx0 + (4.0 * s - 3.0 * nt) / n * vy0,
+        c * z0 + s / n * vz0,
+        3.0 * n * s * x0 + c * vx0 + 2.0 * s * vy0,
+        -6.0 * n * (1.0 - c) * x0 - 2.0 * s * vx0 + (4.0 * c - 3.0) * vy0,
+        -n * s * z0 + c * vz0,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn radial_offset_traces_closed_two_to_one_ellipse() {
+        // Mean motion of a 7000 km orbit around the Earth.
+        let n = (3.986004418e14f64 / 7.0e6f64.powi(3)).sqrt();
+        let x0 = 100.0;
+        let start = [x0, 0.0, 0.0, 0.0, -2.0 * n * x0, 0.0];
+
+        for k in 0..=48 {
+            let t = k as f64 * TAU / n / 48.0;
+            let [x, y, z, ..] = cw_propagate(&start, n, t);
+            let on_ellipse = (x / x0).powi(2) + (y / (2.0 * x0)).powi(2);
+            assert!((on_ellipse - 1.0).abs() < 1e-9, "t {t}: {on_ellipse}");
+            assert_eq!(z, 0.0);
+        }
+
+        let end = cw_propagate(&start, n, TAU / n);
+        for k in 0..6 {
+            assert!((end[k] - start[k]).abs() < 1e-9, "component {k}");
+        }
+    }
+}
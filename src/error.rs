@@ -0,0 +1,95 @@
+// src/error.rs
+
+//! Errors for physically meaningless inputs, returned by the `*_checked` functions.
+
+use std::fmt;
+
+/// An input that would make the physics produce NaN or garbage instead of a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicsError {
+    /// The gravitational parameter is zero, negative or not finite.
+    NonPositiveMu,
+    /// The time step is NaN or infinite.
+    NonFiniteDt,
+    /// A radius (or the semi-latus rectum of an orbit) is zero, negative or not finite.
+    NegativeRadius,
+}
+
+impl fmt::Display for PhysicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicsError::NonPositiveMu => write!(f, "gravitational parameter must be finite and positive"),
+            PhysicsError::NonFiniteDt => write!(f, "time step must be finite"),
+            PhysicsError::NegativeRadius => write!(f, "radius must be finite and positive"),
+        }
+    }
+}
+
+impl std::error::Error for PhysicsError {}
+
+pub(crate) fn check_mu(mu: f64) -> Result<(), PhysicsError> {
+    if mu.is_finite() && mu > 0.0 {
+        Ok(())
+    } else {
+        Err(PhysicsError::NonPositiveMu)
+    }
+}
+
+pub(crate) fn check_dt(dt: f64) -> Result<(), PhysicsError> {
+    if dt.is_finite() {
+        Ok(())
+    } else {
+        Err(PhysicsError::NonFiniteDt)
+    }
+}
+
+pub(crate) fn check_radius(r: f64) -> Result<(), PhysicsError> {
+    if r.is_finite() && r > 0.0 {
+        Ok(())
+    } else {
+        Err(PhysicsError::NegativeRadius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{leapfrog_step_checked, rk4_step_checked, Position, Velocity, World};
+    use crate::orbit::{elements_to_state_checked, state_to_elements_checked, OrbitalElements};
+
+    const MU: f64 = 3.986004418e14;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.add_entity(Position { x: 7.0e6, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: 7.5e3, dz: 0.0 });
+        world
+    }
+
+    #[test]
+    fn zero_mu_is_rejected() {
+        let mut world = world();
+        assert_eq!(rk4_step_checked(&mut world, 10.0, 0.0), Err(PhysicsError::NonPositiveMu));
+        assert_eq!(leapfrog_step_checked(&mut world, 10.0, 0.0), Err(PhysicsError::NonPositiveMu));
+        let (pos, vel) = (&world.positions[0], &world.velocities[0]);
+        assert_eq!(state_to_elements_checked(pos, vel, 0.0), Err(PhysicsError::NonPositiveMu));
+        // Nothing moved.
+        assert_eq!(world.positions[0].x, 7.0e6);
+    }
+
+    #[test]
+    fn nan_dt_is_rejected() {
+        let mut world = world();
+        assert_eq!(rk4_step_checked(&mut world, f64::NAN, MU), Err(PhysicsError::NonFiniteDt));
+        assert_eq!(leapfrog_step_checked(&mut world, f64::NAN, MU), Err(PhysicsError::NonFiniteDt));
+        assert!(rk4_step_checked(&mut world, 10.0, MU).is_ok());
+    }
+
+    #[test]
+    fn degenerate_radii_are_rejected() {
+        let origin = Position { x: 0.0, y: 0.0, z: 0.0 };
+        let vel = Velocity { dx: 0.0, dy: 7.5e3, dz: 0.0 };
+        assert_eq!(state_to_elements_checked(&origin, &vel, MU), Err(PhysicsError::NegativeRadius));
+        let elements = OrbitalElements { a: -7.0e6, e: 0.5, i: 0.0, raan: 0.0, argp: 0.0, nu: 0.0 };
+        assert_eq!(elements_to_state_checked(&elements, MU).err(), Some(PhysicsError::NegativeRadius));
+    }
+}
@@ -0,0 +1,320 @@
+// src/frames.rs
+
+//! Conversions between the inertial (ECI) frame used by the simulation, the Earth-fixed
+//! (ECEF) frame and a chief satellite's radial-tangential-normal (RTN) frame.
+
+use crate::ecs::{EntityId, Position, Velocity, World};
+use std::f64::consts::TAU;
+
+/// Earth's rotation rate relative to the stars, in radians per second.
+pub const EARTH_ROTATION_RATE: f64 = 360.985_647_366_29 / 86400.0 * (TAU / 360.0);
+/// Greenwich Mean Sidereal Time at the J2000 epoch (2000-01-01 12:00), in radians.
+pub const GMST_J2000: f64 = 280.460_618_37 * (TAU / 360.0);
+
+/// Greenwich Mean Sidereal Time in [0, 2π) at `seconds_since_epoch` seconds after J2000.
+pub fn gmst_from_seconds(seconds_since_epoch: f64) -> f64 {
+    (GMST_J2000 + EARTH_ROTATION_RATE * seconds_since_epoch).rem_euclid(TAU)
+}
+
+/// Unit vector from Earth to the Sun in the inertial frame at `epoch_seconds` after J2000.
+///
+/// Uses the low-precision solar ephemeris from the Astronomical Almanac (mean longitude, mean
+/// anomaly and obliquity of the ecliptic), good to about 0.01° between 1950 and 2050.
+pub fn sun_direction(epoch_seconds: f64) -> [f64; 3] {
+    let n = epoch_seconds / 86400.0;
+    let mean_longitude = (280.460 + 0.985_647_4 * n).to_radians();
+    let mean_anomaly = (357.528 + 0.985_600_3 * n).to_radians();
+    let ecliptic_longitude = mean_longitude
+        + 1.915_f64.to_radians() * mean_anomaly.sin()
+        + 0.020_f64.to_radians() * (2.0 * mean_anomaly).sin();
+    let obliquity = (23.439 - 0.000_000_4 * n).to_radians();
+    let (sin_l, cos_l) = ecliptic_longitude.sin_cos();
+    let (sin_e, cos_e) = obliquity.sin_cos();
+    [cos_l, cos_e * sin_l, sin_e * sin_l]
+}
+
+/// Rotates `pos` about the z-axis by `angle` radians.
+fn rotate_z(pos: &Position, angle: f64) -> Position {
+    let (sin, cos) = angle.sin_cos();
+    Position {
+        x: cos * pos.x - sin * pos.y,
+        y: sin * pos.x + cos * pos.y,
+        z: pos.z,
+    }
+}
+
+/// Converts an inertial position to the Earth-fixed frame at sidereal time `gmst`.
+pub fn eci_to_ecef(pos: &Position, gmst: f64) -> Position {
+    rotate_z(pos, -gmst)
+}
+
+/// Converts an Earth-fixed position to the inertial frame at sidereal time `gmst`.
+pub fn ecef_to_eci(pos: &Position, gmst: f64) -> Position {
+    rotate_z(pos, gmst)
+}
+
+/// WGS-84 equatorial radius in meters.
+pub const WGS84_A: f64 = 6378137.0;
+/// WGS-84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257223563;
+/// Convergence tolerance of the geodetic latitude iteration, in radians.
+const GEODETIC_TOLERANCE: f64 = 1e-12;
+/// Iteration cap of the geodetic latitude iteration.
+const GEODETIC_MAX_ITERATIONS: usize = 10;
+
+/// A position on the WGS-84 ellipsoid: latitude in [-π/2, π/2] and longitude in [-π, π]
+/// in radians, altitude above the ellipsoid in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodetic {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt: f64,
+}
+
+/// Converts an Earth-fixed position to geodetic coordinates with Bowring's iterative method.
+pub fn ecef_to_geodetic(pos: &Position) -> Geodetic {
+    let b = WGS84_A * (1.0 - WGS84_F);
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let ep2 = e2 / (1.0 - e2);
+    let p = (pos.x * pos.x + pos.y * pos.y).sqrt();
+    let lon = pos.y.atan2(pos.x);
+
+    // Iterate on the reduced latitude β until the geodetic latitude settles.
+    let mut beta = pos.z.atan2((1.0 - WGS84_F) * p);
+    let mut lat = 0.0;
+    for _ in 0..GEODETIC_MAX_ITERATIONS {
+        let (sin_b, cos_b) = beta.sin_cos();
+        let next = (pos.z + ep2 * b * sin_b * sin_b * sin_b)
+            .atan2(p - e2 * WGS84_A * cos_b * cos_b * cos_b);
+        let converged = (next - lat).abs() < GEODETIC_TOLERANCE;
+        lat = next;
+        if converged {
+            break;
+        }
+        let (sin_l, cos_l) = lat.sin_cos();
+        beta = ((1.0 - WGS84_F) * sin_l).atan2(cos_l);
+    }
+
+    // This form of the height stays accurate near the poles, where p → 0.
+    let (sin_l, cos_l) = lat.sin_cos();
+    let n = WGS84_A / (1.0 - e2 * sin_l * sin_l).sqrt();
+    let alt = p * cos_l + (pos.z + e2 * n * sin_l) * sin_l - n;
+
+    Geodetic { lat, lon, alt }
+}
+
+/// Converts geodetic coordinates on the WGS-84 ellipsoid to an Earth-fixed position.
+pub fn geodetic_to_ecef(geodetic: &Geodetic) -> Position {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (sin_lat, cos_lat) = geodetic.lat.sin_cos();
+    let (sin_lon, cos_lon) = geodetic.lon.sin_cos();
+    let n = WGS84_A / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    Position {
+        x: (n + geodetic.alt) * cos_lat * cos_lon,
+        y: (n + geodetic.alt) * cos_lat * sin_lon,
+        z: (n * (1.0 - e2) + geodetic.alt) * sin_lat,
+    }
+}
+
+/// Returns the sub-satellite point of every entity at sidereal time `gmst`, ordered by id.
+pub fn ground_track(world: &World, gmst: f64) -> Vec<(EntityId, Geodetic)> {
+    world.entities_sorted()
+        .into_iter()
+        .map(|id| {
+            let pos = &world.positions[world.index_of(id).expect("sorted ids are live")];
+            (id, ecef_to_geodetic(&eci_to_ecef(pos, gmst)))
+        })
+        .collect()
+}
+
+/// Unit vectors R (radial), T (along-track) and N (orbit normal) of the chief's RTN frame, and
+/// the frame's rotation rate |r × v| / r² about N. `None` if the chief is at the origin or
+/// moving radially.
+fn rtn_basis(chief_pos: &Position, chief_vel: &Velocity) -> Option<([[f64; 3]; 3], f64)> {
+    let r = [chief_pos.x, chief_pos.y, chief_pos.z];
+    let v = [chief_vel.dx, chief_vel.dy, chief_vel.dz];
+    let h = [
+        r[1] * v[2] - r[2] * v[1],
+        r[2] * v[0] - r[0] * v[2],
+        r[0] * v[1] - r[1] * v[0],
+    ];
+    let r_mag = (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt();
+    let h_mag = (h[0] * h[0] + h[1] * h[1] + h[2] * h[2]).sqrt();
+    if r_mag == 0.0 || h_mag == 0.0 {
+        return None;
+    }
+    let radial = [r[0] / r_mag, r[1] / r_mag, r[2] / r_mag];
+    let normal = [h[0] / h_mag, h[1] / h_mag, h[2] / h_mag];
+    let along = [
+        normal[1] * radial[2] - normal[2] * radial[1],
+        normal[2] * radial[0] - normal[0] * radial[2],
+        normal[0] * radial[1] - normal[1] * radial[0],
+    ];
+    Some(([radial, along, normal], h_mag / (r_mag * r_mag)))
+}
+
+/// Returns the target's state relative to the chief, `[x, y, z, vx, vy, vz]` in the chief's
+/// rotating RTN frame: x radial, y along-track and z along the orbit normal, the same layout
+/// [`crate::cw::cw_propagate`] uses.
+///
+/// The velocity is measured in the rotating frame, so a target fixed relative to the chief has
+/// zero relative velocity. Returns NaNs if the chief is at the origin or moving radially,
+/// where the frame is undefined.
+pub fn eci_to_rtn(chief_pos: &Position, chief_vel: &Velocity, target_pos: &Position, target_vel: &Velocity) -> [f64; 6] {
+    let Some((basis, omega)) = rtn_basis(chief_pos, chief_vel) else {
+        return [f64::NAN; 6];
+    };
+    let dr = [target_pos.x - chief_pos.x, target_pos.y - chief_pos.y, target_pos.z - chief_pos.z];
+    let dv = [target_vel.dx - chief_vel.dx, target_vel.dy - chief_vel.dy, target_vel.dz - chief_vel.dz];
+    let project = |u: [f64; 3]| basis.map(|axis| axis[0] * u[0] + axis[1] * u[1] + axis[2] * u[2]);
+    let rho = project(dr);
+    let rho_dot = project(dv);
+    // Remove the frame rotation: v_rot = v_inertial - ω × ρ with ω = (0, 0, omega).
+    [
+        rho[0],
+        rho[1],
+        rho[2],
+        rho_dot[0] + omega * rho[1],
+        rho_dot[1] - omega * rho[0],
+        rho_dot[2],
+    ]
+}
+
+/// Inverse of [`eci_to_rtn`]: returns the target's inertial position and velocity from its
+/// state `rel` relative to the chief in the chief's RTN frame.
+pub fn rtn_to_eci(chief_pos: &Position, chief_vel: &Velocity, rel: &[f64; 6]) -> (Position, Velocity) {
+    let Some((basis, omega)) = rtn_basis(chief_pos, chief_vel) else {
+        return (
+            Position { x: f64::NAN, y: f64::NAN, z: f64::NAN },
+            Velocity { dx: f64::NAN, dy: f64::NAN, dz: f64::NAN },
+        );
+    };
+    let [x, y, z, vx, vy, vz] = *rel;
+    // Add the frame rotation back: v_inertial = v_rot + ω × ρ.
+    let rho_dot = [vx - omega * y, vy + omega * x, vz];
+    let unproject = |u: [f64; 3]| {
+        [0, 1, 2].map(|k| basis[0][k] * u[0] + basis[1][k] * u[1] + basis[2][k] * u[2])
+    };
+    let dr = unproject([x, y, z]);
+    let dv = unproject(rho_dot);
+    (
+        Position { x: chief_pos.x + dr[0], y: chief_pos.y + dr[1], z: chief_pos.z + dr[2] },
+        Velocity { dx: chief_vel.dx + dv[0], dy: chief_vel.dy + dv[1], dz: chief_vel.dz + dv[2] },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &Position, b: &Position, tol: f64) -> bool {
+        (a.x - b.x).abs() < tol && (a.y - b.y).abs() < tol && (a.z - b.z).abs() < tol
+    }
+
+    #[test]
+    fn equator_point_turns_a_quarter_in_a_quarter_sidereal_day() {
+        let sidereal_day = TAU / EARTH_ROTATION_RATE;
+        let site = Position { x: WGS84_A, y: 0.0, z: 0.0 };
+        let start = ecef_to_eci(&site, gmst_from_seconds(1.0e6));
+        let later = ecef_to_eci(&site, gmst_from_seconds(1.0e6 + sidereal_day / 4.0));
+
+        // A quarter turn east about z maps (x, y) to (-y, x).
+        let expected = Position { x: -start.y, y: start.x, z: 0.0 };
+        assert!(close(&later, &expected, 1e-3));
+        assert!(close(&eci_to_ecef(&later, gmst_from_seconds(1.0e6 + sidereal_day / 4.0)), &site, 1e-6));
+    }
+
+    #[test]
+    fn geodetic_at_equator_pole_and_paris() {
+        let equator = ecef_to_geodetic(&Position { x: WGS84_A + 1000.0, y: 0.0, z: 0.0 });
+        assert_eq!((equator.lat, equator.lon), (0.0, 0.0));
+        assert!((equator.alt - 1000.0).abs() < 1e-6);
+
+        let pole = ecef_to_geodetic(&Position { x: 0.0, y: 0.0, z: WGS84_A * (1.0 - WGS84_F) });
+        assert!((pole.lat - TAU / 4.0).abs() < 1e-12);
+        assert!(pole.alt.abs() < 1e-6);
+
+        let west = ecef_to_geodetic(&Position { x: -WGS84_A, y: -1.0, z: 0.0 });
+        assert!(west.lon < 0.0 && (west.lon + TAU / 2.0).abs() < 1e-6);
+
+        // Top of the Eiffel Tower, 48.8584° N 2.2945° E at 300 m.
+        let paris = ecef_to_geodetic(&Position { x: 4201133.037, y: 168331.004, z: 4780438.968 });
+        assert!((paris.lat.to_degrees() - 48.8584).abs() < 1e-8);
+        assert!((paris.lon.to_degrees() - 2.2945).abs() < 1e-8);
+        assert!((paris.alt - 300.0).abs() < 1e-2);
+        assert!(close(&geodetic_to_ecef(&paris), &Position { x: 4201133.037, y: 168331.004, z: 4780438.968 }, 1e-6));
+    }
+
+    #[test]
+    fn equatorial_ground_track_stays_on_equator_and_moves_east() {
+        const MU: f64 = 3.986004418e14;
+        let r = 7.0e6;
+        let mut world = World::new();
+        world.add_entity(Position { x: r, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: (MU / r).sqrt(), dz: 0.0 });
+
+        let mut previous = ground_track(&world, gmst_from_seconds(0.0))[0].1;
+        for k in 1..=60 {
+            crate::ecs::leapfrog_step(&mut world, 60.0, MU);
+            let track = ground_track(&world, gmst_from_seconds(60.0 * k as f64));
+            assert_eq!(track.len(), 1);
+            let point = track[0].1;
+            assert!(point.lat.abs() < 1e-9);
+            // A LEO satellite outruns the Earth's rotation, so its longitude keeps increasing.
+            let advance = (point.lon - previous.lon).rem_euclid(TAU);
+            assert!(advance > 0.0 && advance < 0.3);
+            previous = point;
+        }
+    }
+
+    #[test]
+    fn sun_declination_swings_by_the_obliquity_over_a_year() {
+        let z: Vec<f64> = (0..366)
+            .map(|day| {
+                let s = sun_direction(day as f64 * 86400.0);
+                assert!(((s[0] * s[0] + s[1] * s[1] + s[2] * s[2]).sqrt() - 1.0).abs() < 1e-12);
+                s[2]
+            })
+            .collect();
+        let obliquity = 23.439f64.to_radians().sin();
+        let max = z.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = z.iter().copied().fold(f64::INFINITY, f64::min);
+        assert!((max - obliquity).abs() < 1e-3, "max {max}");
+        assert!((min + obliquity).abs() < 1e-3, "min {min}");
+    }
+
+    #[test]
+    fn rtn_round_trip_recovers_relative_state() {
+        let v = (3.986004418e14_f64 / 7.0e6).sqrt();
+        let chief_pos = Position { x: 7.0e6, y: 1.0e5, z: -2.0e5 };
+        let chief_vel = Velocity { dx: -50.0, dy: 0.8 * v, dz: 0.6 * v };
+        let target_pos = Position { x: 7.0e6 + 350.0, y: 1.0e5 - 1200.0, z: -2.0e5 + 80.0 };
+        let target_vel = Velocity { dx: -48.5, dy: 0.8 * v + 0.3, dz: 0.6 * v - 1.1 };
+
+        let rel = eci_to_rtn(&chief_pos, &chief_vel, &target_pos, &target_vel);
+        let (pos, vel) = rtn_to_eci(&chief_pos, &chief_vel, &rel);
+
+        let expected = [
+            target_pos.x - chief_pos.x,
+            target_pos.y - chief_pos.y,
+            target_pos.z - chief_pos.z,
+            target_vel.dx - chief_vel.dx,
+            target_vel.dy - chief_vel.dy,
+            target_vel.dz - chief_vel.dz,
+        ];
+        let recovered = [
+            pos.x - chief_pos.x,
+            pos.y - chief_pos.y,
+            pos.z - chief_pos.z,
+            vel.dx - chief_vel.dx,
+            vel.dy - chief_vel.dy,
+            vel.dz - chief_vel.dz,
+        ];
+        for (a, b) in recovered.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-9, "{recovered:?} vs {expected:?}");
+        }
+        // A target co-moving with the chief's rotating frame has no relative velocity.
+        let (pos, vel) = rtn_to_eci(&chief_pos, &chief_vel, &[100.0, -50.0, 20.0, 0.0, 0.0, 0.0]);
+        let back = eci_to_rtn(&chief_pos, &chief_vel, &pos, &vel);
+        assert!(back[3..].iter().all(|c| c.abs() < 1e-9));
+    }
+}
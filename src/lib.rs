@@ -1,20 +1,90 @@
 // src/lib.rs
 
+// `ecs::EntityId` is deprecated for callers, but stays the crate's own id type until handles
+// replace it throughout the public API.
+#![allow(deprecated)]
+
 
 // Re-export the wasm interface so that its exports are available at the top level.
 pub mod wasm_interface;
 pub use wasm_interface::*;
 
+pub mod orbit;
+pub mod conjunction;
+pub mod constellation;
+pub mod tle;
+pub mod frames;
+pub mod maneuver;
+pub mod simulator;
+pub mod cr3bp;
+pub mod visibility;
+pub mod export;
+pub mod error;
+pub mod cw;
+pub mod octree;
+
 
 
 /// This module contains the core ECS implementation using Vec storage and Rayon for parallelism.
+///
+/// Parallelism is controlled by the default `rayon` feature; without it every system runs serially.
+/// Per-entity systems (central gravity, propagation, the integrators and perturbations) only read
+/// and write each entity's own components, so they produce bit-identical results either way.
 pub mod ecs {
+    #[cfg(feature = "rayon")]
     use rayon::prelude::*;
-    use std::collections::HashSet;
+    #[cfg(not(feature = "rayon"))]
+    use serial::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::any::{Any, TypeId};
+    use std::cmp::Ordering;
+    use std::collections::{BinaryHeap, HashMap, HashSet};
     use std::sync::Mutex;
+    use crate::error::{check_dt, check_mu, PhysicsError};
+
+    /// Serial stand-ins for the rayon iterator methods used by the systems below,
+    /// so they compile unchanged when the `rayon` feature is disabled.
+    #[cfg(not(feature = "rayon"))]
+    mod serial {
+        pub trait ParallelSlice<T> {
+            fn par_iter(&self) -> std::slice::Iter<'_, T>;
+            fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T>;
+        }
+
+        impl<T> ParallelSlice<T> for [T] {
+            fn par_iter(&self) -> std::slice::Iter<'_, T> {
+                self.iter()
+            }
+
+            fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+                self.iter_mut()
+            }
+        }
+
+        pub trait IntoParallelIterator: IntoIterator + Sized {
+            fn into_par_iter(self) -> Self::IntoIter {
+                self.into_iter()
+            }
+        }
+
+        impl<I: IntoIterator> IntoParallelIterator for I {}
+
+        pub trait ParallelIterator: Iterator + Sized {
+            fn flat_map_iter<U, F>(self, f: F) -> std::iter::FlatMap<Self, U, F>
+            where
+                U: IntoIterator,
+                F: FnMut(Self::Item) -> U,
+            {
+                self.flat_map(f)
+            }
+        }
 
+        impl<I: Iterator> ParallelIterator for I {}
+    }
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Position {
         pub x: f64,
         pub y: f64,
@@ -22,60 +92,1070 @@ pub mod ecs {
     }
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Velocity {
         pub dx: f64,
         pub dy: f64,
         pub dz: f64,
     }
 
+    /// Magnitude of a velocity in m/s.
+    pub fn speed(vel: &Velocity) -> f64 {
+        (vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz).sqrt()
+    }
+
+    /// Acceleration accumulated by the force systems while [`World::accumulate_forces`] is set,
+    /// applied and cleared by [`integrate_system`].
+    #[derive(Debug, Clone, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Acceleration {
+        pub ax: f64,
+        pub ay: f64,
+        pub az: f64,
+    }
+
+    /// Mass of an entity in kilograms, used by the N-body gravity system.
+    #[derive(Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Mass(pub f64);
+
+    /// The primary an entity orbits, overriding the default gravitational parameter
+    /// passed to [`gravity_system`].
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct CentralBody {
+        /// Gravitational parameter of the body (m³/s²).
+        pub mu: f64,
+        pub position: Position,
+    }
+
+    /// A massive body at a fixed position, registered on the world with
+    /// [`World::add_gravity_body`] and felt by every entity in [`gravity_system`].
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct GravityBody {
+        /// Gravitational parameter of the body (m³/s²).
+        pub mu: f64,
+        pub position: Position,
+    }
+
+    /// Aerodynamic properties used by the drag system.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DragProperties {
+        /// Drag coefficient (dimensionless).
+        pub cd: f64,
+        /// Cross-sectional area in m².
+        pub area: f64,
+        /// Mass in kg.
+        pub mass: f64,
+    }
+
+    /// Radiation pressure coefficient Cr (1 = fully absorbing, 2 = fully reflecting),
+    /// used together with the area and mass in [`DragProperties`] by the SRP system.
+    #[derive(Debug, Clone, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Reflectivity(pub f64);
+
+    /// Number of periapsis passages counted by [`revolution_system`] since the component was attached.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct RevolutionCount(pub u32);
+
+    /// True anomaly seen by the previous [`revolution_system`] call.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    struct PreviousAnomaly(f64);
+
+    /// Free-form tag for an entity, such as its operator or constellation name.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Label(pub String);
+
+    /// Direction a [`Thruster`] pushes in.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ThrustFrame {
+        /// Along the instantaneous velocity.
+        Prograde,
+        /// Away from the origin, along the position vector.
+        Radial,
+        /// Along a fixed inertial direction, which need not be normalized.
+        Fixed([f64; 3]),
+    }
+
+    /// Continuous low-thrust propulsion, applied by [`thrust_system`] while `enabled`.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Thruster {
+        pub direction: ThrustFrame,
+        /// Thrust acceleration in m/s².
+        pub acceleration: f64,
+        pub enabled: bool,
+    }
+
+    /// One window of thrust in a [`BurnSchedule`], active for `start <= t < end` (s on the world clock).
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BurnSegment {
+        pub start: f64,
+        pub end: f64,
+        pub direction: ThrustFrame,
+        /// Thrust acceleration in m/s².
+        pub accel: f64,
+    }
+
+    /// Scripted burns applied by [`scheduled_thrust_system`].
+    #[derive(Debug, Clone, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BurnSchedule {
+        pub segments: Vec<BurnSegment>,
+    }
+
+    /// Keeps an entity at a fixed inertial `offset` (m) from its `leader`, see [`formation_system`].
+    ///
+    /// The leader is held by [`Entity`] handle, so a follower whose leader is removed does not
+    /// latch onto a later entity that reuses the id.
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct FormationTarget {
+        pub leader: Entity,
+        pub offset: [f64; 3],
+    }
+
+    /// Total delta-v (m/s) an entity has spent, added to by [`crate::maneuver::apply_impulse`],
+    /// [`thrust_system`] and [`scheduled_thrust_system`]. Attached on the first burn; see [`World::total_delta_v`].
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DeltaVBudget(pub f64);
+
+    /// Raw entity index, kept as an alias during the move to [`Entity`] handles.
+    ///
+    /// Ids of removed entities are handed out again by [`World::add_entity`], so a raw id kept
+    /// across a removal may end up naming a newer entity; hold on to an [`Entity`] handle instead
+    /// when the id needs to stay valid. The id's slot in the component vectors also moves as
+    /// other entities are removed, so look it up with [`World::index_of`] rather than keeping it.
+    #[deprecated(note = "raw ids are reused after removal; hold an `Entity` handle instead")]
     pub type EntityId = usize;
 
+    /// A generational handle to an entity.
+    ///
+    /// The generation is bumped every time the index is freed, so a handle to a removed entity
+    /// stays dead even after its index has been handed out again.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Entity {
+        index: u32,
+        generation: u32,
+    }
+
+    impl Entity {
+        /// The raw id this handle refers to.
+        pub fn id(&self) -> EntityId {
+            self.index as EntityId
+        }
+
+        /// The generation of the index at the time the handle was created.
+        pub fn generation(&self) -> u32 {
+            self.generation
+        }
+    }
+
+    /// Type-erased storage for one registered component type.
+    trait ComponentStorage: Any + Send + Sync {
+        fn remove(&mut self, entity: EntityId);
+        fn clear(&mut self);
+        fn as_any(&self) -> &dyn Any;
+        fn as_any_mut(&mut self) -> &mut dyn Any;
+        fn clone_box(&self) -> Box<dyn ComponentStorage>;
+        fn len(&self) -> usize;
+        fn contains(&self, entity: EntityId) -> bool;
+        fn ids(&self) -> Vec<EntityId>;
+    }
+
+    impl<T: Clone + Send + Sync + 'static> ComponentStorage for HashMap<EntityId, T> {
+        fn remove(&mut self, entity: EntityId) {
+            HashMap::remove(self, &entity);
+        }
+
+        fn clear(&mut self) {
+            HashMap::clear(self);
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn clone_box(&self) -> Box<dyn ComponentStorage> {
+            Box::new(self.clone())
+        }
+
+        fn len(&self) -> usize {
+            HashMap::len(self)
+        }
+
+        fn contains(&self, entity: EntityId) -> bool {
+            self.contains_key(&entity)
+        }
+
+        fn ids(&self) -> Vec<EntityId> {
+            self.keys().copied().collect()
+        }
+    }
+
+    fn clone_components(
+        components: &HashMap<TypeId, Box<dyn ComponentStorage>>,
+    ) -> HashMap<TypeId, Box<dyn ComponentStorage>> {
+        components.iter().map(|(&id, s)| (id, s.clone_box())).collect()
+    }
+
+    /// Looks up the storage for `T`, if the component type has been registered.
+    fn storage<T: Send + Sync + 'static>(
+        components: &HashMap<TypeId, Box<dyn ComponentStorage>>,
+    ) -> Option<&HashMap<EntityId, T>> {
+        components
+            .get(&TypeId::of::<T>())
+            .and_then(|s| s.as_any().downcast_ref())
+    }
+
+    /// Serializes the storages of the component types defined in this crate, each under its own
+    /// name and keyed by entity id. Storages of other types are skipped, since their type is only
+    /// known to the code that registered them.
+    #[cfg(feature = "serde")]
+    mod component_serde {
+        use super::*;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use std::collections::BTreeMap;
+
+        macro_rules! builtin_components {
+            ($($field:ident: $ty:ty),* $(,)?) => {
+                #[derive(Serialize)]
+                struct Stored<'a> {
+                    $(
+                        #[serde(skip_serializing_if = "Option::is_none")]
+                        $field: Option<BTreeMap<EntityId, &'a $ty>>,
+                    )*
+                }
+
+                #[derive(Deserialize)]
+                #[serde(deny_unknown_fields)]
+                struct Owned {
+                    $(
+                        #[serde(default)]
+                        $field: Option<HashMap<EntityId, $ty>>,
+                    )*
+                }
+
+                pub fn serialize<S: Serializer>(
+                    components: &HashMap<TypeId, Box<dyn ComponentStorage>>,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    Stored {
+                        $($field: storage::<$ty>(components).map(|s| s.iter().map(|(&id, c)| (id, c)).collect()),)*
+                    }
+                    .serialize(serializer)
+                }
+
+                pub fn deserialize<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<HashMap<TypeId, Box<dyn ComponentStorage>>, D::Error> {
+                    let owned = Owned::deserialize(deserializer)?;
+                    let mut components: HashMap<TypeId, Box<dyn ComponentStorage>> = HashMap::new();
+                    $(
+                        if let Some(storage) = owned.$field {
+                            components.insert(TypeId::of::<$ty>(), Box::new(storage));
+                        }
+                    )*
+                    Ok(components)
+                }
+            };
+        }
+
+        builtin_components! {
+            mass: Mass,
+            central_body: CentralBody,
+            drag_properties: DragProperties,
+            reflectivity: Reflectivity,
+            revolution_count: RevolutionCount,
+            previous_anomaly: PreviousAnomaly,
+            label: Label,
+            thruster: Thruster,
+            burn_schedule: BurnSchedule,
+            formation_target: FormationTarget,
+            delta_v_budget: DeltaVBudget,
+        }
+    }
+
+    /// Edge behavior of the cube [-L, L]³ centered on the origin, for running the world as a
+    /// generic particle simulation rather than around a central body.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum BoundaryMode {
+        /// Unbounded space.
+        #[default]
+        None,
+        /// Entities bounce off the walls at half-width L: the position is mirrored back inside
+        /// and the velocity component across the wall is reversed.
+        Reflect(f64),
+        /// Entities leaving through a wall at half-width L re-enter through the opposite one
+        /// with their velocity unchanged.
+        Wrap(f64),
+    }
+
+    /// Filter over the entities that carry a set of components, started by [`World::query`].
+    ///
+    /// [`Position`], [`Velocity`] and [`Acceleration`] are stored for every entity, so they
+    /// always match; every other type must be attached with [`World::insert_component`].
+    pub struct Query<'w> {
+        world: &'w World,
+        types: Vec<TypeId>,
+    }
+
+    impl Query<'_> {
+        /// Restricts the query to entities that carry a `T`.
+        pub fn with<T: 'static>(mut self) -> Self {
+            let id = TypeId::of::<T>();
+            let dense = [TypeId::of::<Position>(), TypeId::of::<Velocity>(), TypeId::of::<Acceleration>()];
+            if !dense.contains(&id) {
+                self.types.push(id);
+            }
+            self
+        }
+
+        /// Returns the ids of the matching entities in ascending order.
+        pub fn iter(&self) -> std::vec::IntoIter<EntityId> {
+            let storages: Option<Vec<&dyn ComponentStorage>> = self.types
+                .iter()
+                .map(|id| self.world.components.get(id).map(|s| s.as_ref()))
+                .collect();
+            let Some(storages) = storages else {
+                return Vec::new().into_iter();
+            };
+
+            // Walk the smallest storage and check the others, rather than every entity.
+            let mut ids = match storages.iter().min_by_key(|s| s.len()) {
+                Some(smallest) => smallest
+                    .ids()
+                    .into_iter()
+                    .filter(|&id| self.world.index_of(id).is_some())
+                    .filter(|&id| storages.iter().all(|s| s.contains(id)))
+                    .collect(),
+                None => self.world.entities.clone(),
+            };
+            ids.sort_unstable();
+            ids.into_iter()
+        }
+    }
+
+    /// Callback invoked for each [`ProximityWarning`], see [`World::set_proximity_callback`].
+    pub type ProximityCallback = Box<dyn FnMut(&ProximityWarning) + Send>;
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct World {
         pub positions: Vec<Position>,
         pub velocities: Vec<Velocity>,
+        pub accelerations: Vec<Acceleration>,
+        /// Entity id stored at each index of the component vectors.
+        pub entities: Vec<EntityId>,
+        /// Index into the component vectors for every id below `next_entity`, `None` once removed.
+        pub indices: Vec<Option<usize>>,
         pub proximity_warnings: HashSet<usize>,
+        /// One past the highest id ever handed out.
+        pub next_entity: EntityId,
+        /// Current generation of every id below `next_entity`.
+        pub generations: Vec<u32>,
+        /// Ids of removed entities, reused by [`World::add_entity`] before `next_entity` grows.
+        pub free_ids: Vec<EntityId>,
+        /// Simulation time in seconds, advanced by [`World::advance_time`].
+        pub epoch_seconds: f64,
+        /// Bodies whose combined field [`gravity_system`] applies, replacing the default primary.
+        pub gravity_bodies: Vec<GravityBody>,
+        /// When set, force systems add to `accelerations` instead of updating velocities,
+        /// and [`integrate_system`] applies the sum.
+        pub accumulate_forces: bool,
+        /// Box boundary enforced by [`propagate_system`].
+        pub boundary: BoundaryMode,
+        /// Storages for user-registered component types, keyed by the component's type.
+        ///
+        /// These are type-erased, so only the component types defined in this crate are
+        /// serialized, see [`World::to_json`].
+        #[cfg_attr(feature = "serde", serde(default, with = "component_serde"))]
+        components: HashMap<TypeId, Box<dyn ComponentStorage>>,
+        /// Called by the proximity systems for every warning they return.
+        ///
+        /// Behind a mutex so the systems can call it through `&World`.
+        #[cfg_attr(feature = "serde", serde(skip))]
+        proximity_callback: Mutex<Option<ProximityCallback>>,
+        /// Force systems run in order by [`World::step_all`].
+        #[cfg_attr(feature = "serde", serde(skip))]
+        force_systems: Vec<Box<dyn ForceSystem>>,
         // ... other fields
     }
 
+    /// In-memory copy of a world's entities and components, taken by [`World::snapshot`].
+    pub struct WorldSnapshot {
+        positions: Vec<Position>,
+        velocities: Vec<Velocity>,
+        accelerations: Vec<Acceleration>,
+        entities: Vec<EntityId>,
+        indices: Vec<Option<usize>>,
+        proximity_warnings: HashSet<usize>,
+        next_entity: EntityId,
+        generations: Vec<u32>,
+        free_ids: Vec<EntityId>,
+        epoch_seconds: f64,
+        gravity_bodies: Vec<GravityBody>,
+        components: HashMap<TypeId, Box<dyn ComponentStorage>>,
+    }
+
+    impl Clone for WorldSnapshot {
+        fn clone(&self) -> Self {
+            Self {
+                positions: self.positions.clone(),
+                velocities: self.velocities.clone(),
+                accelerations: self.accelerations.clone(),
+                entities: self.entities.clone(),
+                indices: self.indices.clone(),
+                proximity_warnings: self.proximity_warnings.clone(),
+                next_entity: self.next_entity,
+                generations: self.generations.clone(),
+                free_ids: self.free_ids.clone(),
+                epoch_seconds: self.epoch_seconds,
+                gravity_bodies: self.gravity_bodies.clone(),
+                components: clone_components(&self.components),
+            }
+        }
+    }
+
+    impl Default for World {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl World {
         /// Creates a new, empty world.
         pub fn new() -> Self {
             Self {
                 positions: Vec::new(),
                 velocities: Vec::new(),
+                accelerations: Vec::new(),
+                entities: Vec::new(),
+                indices: Vec::new(),
                 proximity_warnings: std::collections::HashSet::new(),
+                next_entity: 0,
+                generations: Vec::new(),
+                free_ids: Vec::new(),
+                epoch_seconds: 0.0,
+                gravity_bodies: Vec::new(),
+                accumulate_forces: false,
+                boundary: BoundaryMode::None,
+                components: HashMap::new(),
+                proximity_callback: Mutex::new(None),
+                force_systems: Vec::new(),
             }
         }
 
         /// Adds a new entity with a position and velocity, returning its entity id.
+        ///
+        /// ```
+        /// use hylaean_path::ecs::{gravity_system, Position, Velocity, World};
+        ///
+        /// let mut world = World::new();
+        /// let id = world.add_entity(
+        ///     Position { x: 7.0e6, y: 0.0, z: 0.0 },
+        ///     Velocity { dx: 0.0, dy: 7.5e3, dz: 0.0 },
+        /// );
+        /// gravity_system(&mut world, 10.0, 3.986004418e14);
+        ///
+        /// assert_eq!(id, 0);
+        /// assert!(world.velocities[0].dx < 0.0); // pulled back towards the origin
+        /// ```
         pub fn add_entity(&mut self, position: Position, velocity: Velocity) -> EntityId {
+            let entity = self.free_ids.pop().unwrap_or_else(|| {
+                self.generations.push(0);
+                self.indices.push(None);
+                self.next_entity += 1;
+                self.next_entity - 1
+            });
+            self.indices[entity] = Some(self.entities.len());
             self.positions.push(position);
             self.velocities.push(velocity);
-            self.positions.len() - 1 // entity id is the last index
+            self.accelerations.push(Acceleration::default());
+            self.entities.push(entity);
+            entity
+        }
+
+        /// Removes an entity and all of its components, returning whether it existed.
+        ///
+        /// The last entity in the component vectors is moved into the freed slot, and the id is
+        /// queued for reuse with a new generation, so [`Entity`] handles to it go dead.
+        pub fn remove_entity(&mut self, entity: EntityId) -> bool {
+            let Some(index) = self.indices.get(entity).copied().flatten() else {
+                return false;
+            };
+            self.positions.swap_remove(index);
+            self.velocities.swap_remove(index);
+            self.accelerations.swap_remove(index);
+            self.entities.swap_remove(index);
+            if let Some(&moved) = self.entities.get(index) {
+                self.indices[moved] = Some(index);
+            }
+            self.indices[entity] = None;
+            self.proximity_warnings.remove(&entity);
+            for storage in self.components.values_mut() {
+                storage.remove(entity);
+            }
+            self.generations[entity] = self.generations[entity].wrapping_add(1);
+            self.free_ids.push(entity);
+            true
+        }
+
+        /// Removes every entity from the world.
+        ///
+        /// Every removed id gets a new generation, so existing [`Entity`] handles are all
+        /// invalidated. All ids are then free, and new entities are numbered from 0 again.
+        pub fn despawn_all(&mut self) {
+            for &entity in &self.entities {
+                self.generations[entity] = self.generations[entity].wrapping_add(1);
+                self.indices[entity] = None;
+            }
+            // Popped from the back, so the lowest ids come out first.
+            self.free_ids.clear();
+            self.free_ids.extend((0..self.next_entity).rev());
+            self.positions.clear();
+            self.velocities.clear();
+            self.accelerations.clear();
+            self.entities.clear();
+            self.proximity_warnings.clear();
+            for storage in self.components.values_mut() {
+                storage.clear();
+            }
         }
+
+        /// Returns a generational handle for a live entity id, or `None` if the id is dead or does
+        /// not fit in the handle's 32-bit index.
+        pub fn entity(&self, id: EntityId) -> Option<Entity> {
+            self.index_of(id)?;
+            Some(Entity {
+                index: u32::try_from(id).ok()?,
+                generation: self.generations[id],
+            })
+        }
+
+        /// Returns whether the entity behind the handle has not been removed.
+        pub fn is_alive(&self, e: Entity) -> bool {
+            self.component_index(e).is_some()
+        }
+
+        /// Index of a live entity's components in the component vectors.
+        pub fn index_of(&self, id: EntityId) -> Option<usize> {
+            self.indices.get(id).copied().flatten()
+        }
+
+        /// Same as [`World::index_of`], but `None` if the handle is stale.
+        fn component_index(&self, e: Entity) -> Option<usize> {
+            if self.generations.get(e.id()) != Some(&e.generation) {
+                return None;
+            }
+            self.index_of(e.id())
+        }
+
+        /// Returns the entity's position, or `None` if the handle is dead.
+        pub fn position(&self, e: Entity) -> Option<&Position> {
+            self.component_index(e).map(|i| &self.positions[i])
+        }
+
+        /// Returns the entity's position mutably, or `None` if the handle is dead.
+        pub fn position_mut(&mut self, e: Entity) -> Option<&mut Position> {
+            self.component_index(e).map(|i| &mut self.positions[i])
+        }
+
+        /// Returns the entity's velocity, or `None` if the handle is dead.
+        pub fn velocity(&self, e: Entity) -> Option<&Velocity> {
+            self.component_index(e).map(|i| &self.velocities[i])
+        }
+
+        /// Returns the entity's velocity mutably, or `None` if the handle is dead.
+        pub fn velocity_mut(&mut self, e: Entity) -> Option<&mut Velocity> {
+            self.component_index(e).map(|i| &mut self.velocities[i])
+        }
+
+        /// Returns the ids of all live entities in ascending order.
+        ///
+        /// Storage order changes as entities are removed, so outputs that should not depend on
+        /// the removal history are ordered by this instead.
+        pub fn entities_sorted(&self) -> Vec<EntityId> {
+            let mut ids = self.entities.clone();
+            ids.sort_unstable();
+            ids
+        }
+
+        /// Iterates over every entity with its position and velocity, in storage order.
+        pub fn iter_mobile(&self) -> impl Iterator<Item = (EntityId, &Position, &Velocity)> {
+            self.entities
+                .iter()
+                .zip(&self.positions)
+                .zip(&self.velocities)
+                .map(|((&entity, pos), vel)| (entity, pos, vel))
+        }
+
+        /// Returns the speed of every entity in m/s, ordered by id.
+        pub fn speeds(&self) -> Vec<(EntityId, f64)> {
+            self.entities_sorted()
+                .into_iter()
+                .map(|id| (id, speed(&self.velocities[self.index_of(id).expect("sorted ids are live")])))
+                .collect()
+        }
+
+        /// Checks that every position and velocity component is finite.
+        ///
+        /// Returns the ids of the offending entities in ascending order otherwise, so a driver can
+        /// stop or remove them before NaN or infinity spreads through the N-body and proximity systems.
+        pub fn validate(&self) -> Result<(), Vec<EntityId>> {
+            let mut invalid: Vec<EntityId> = self.iter_mobile()
+                .filter(|(_, p, v)| {
+                    ![p.x, p.y, p.z, v.dx, v.dy, v.dz].iter().all(|c| c.is_finite())
+                })
+                .map(|(id, _, _)| id)
+                .collect();
+            if invalid.is_empty() {
+                return Ok(());
+            }
+            invalid.sort_unstable();
+            Err(invalid)
+        }
+
+        /// Starts a [`Query`] over the entities that carry a set of component types.
+        ///
+        /// ```
+        /// use hylaean_path::ecs::{Mass, Position, Velocity, World};
+        ///
+        /// let mut world = World::new();
+        /// let a = world.add_entity(Position { x: 0.0, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: 0.0, dz: 0.0 });
+        /// world.add_entity(Position { x: 1.0, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: 0.0, dz: 0.0 });
+        /// world.insert_component(a, Mass(100.0));
+        ///
+        /// let heavy: Vec<_> = world.query().with::<Position>().with::<Mass>().iter().collect();
+        /// assert_eq!(heavy, vec![a]);
+        /// ```
+        pub fn query(&self) -> Query<'_> {
+            Query { world: self, types: Vec::new() }
+        }
+
+        /// Mutable version of [`World::iter_mobile`].
+        pub fn iter_mobile_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Position, &mut Velocity)> {
+            self.entities
+                .iter()
+                .zip(&mut self.positions)
+                .zip(&mut self.velocities)
+                .map(|((&entity, pos), vel)| (entity, pos, vel))
+        }
+
+        /// Serializes the world to a JSON string: every entity with its position, velocity,
+        /// acceleration and the components whose types are defined in this crate (such as
+        /// [`Mass`], [`Label`] or [`DeltaVBudget`]), the id bookkeeping, the clock, the gravity
+        /// bodies and the settings. Component types defined elsewhere, the proximity callback
+        /// and the force systems are left out.
+        ///
+        /// JSON cannot represent NaN or infinity, so this fails if the clock or any entity's
+        /// position, velocity or acceleration is not finite, instead of writing a file that
+        /// [`World::from_json`] cannot read.
+        #[cfg(feature = "serde")]
+        pub fn to_json(&self) -> Result<String, serde_json::Error> {
+            let mut invalid = self.validate().err().unwrap_or_default();
+            invalid.extend(
+                self.accelerations
+                    .iter()
+                    .zip(&self.entities)
+                    .filter(|(a, _)| ![a.ax, a.ay, a.az].iter().all(|c| c.is_finite()))
+                    .map(|(_, &id)| id),
+            );
+            if !invalid.is_empty() || !self.epoch_seconds.is_finite() {
+                invalid.sort_unstable();
+                invalid.dedup();
+                return Err(serde::ser::Error::custom(format!(
+                    "cannot write non-finite state to JSON (clock {}, entities {invalid:?})",
+                    self.epoch_seconds
+                )));
+            }
+            serde_json::to_string(self)
+        }
+
+        /// Restores a world produced by [`World::to_json`].
+        #[cfg(feature = "serde")]
+        pub fn from_json(s: &str) -> Result<World, serde_json::Error> {
+            serde_json::from_str(s)
+        }
+
+        /// Copies every entity, its components, the clock and the gravity bodies, so the world can
+        /// be rolled back with [`World::restore`] without going through serialization.
+        ///
+        /// Settings that are not simulation state (the proximity callback, the force systems,
+        /// [`World::accumulate_forces`] and [`World::boundary`]) are not part of the snapshot.
+        pub fn snapshot(&self) -> WorldSnapshot {
+            WorldSnapshot {
+                positions: self.positions.clone(),
+                velocities: self.velocities.clone(),
+                accelerations: self.accelerations.clone(),
+                entities: self.entities.clone(),
+                indices: self.indices.clone(),
+                proximity_warnings: self.proximity_warnings.clone(),
+                next_entity: self.next_entity,
+                generations: self.generations.clone(),
+                free_ids: self.free_ids.clone(),
+                epoch_seconds: self.epoch_seconds,
+                gravity_bodies: self.gravity_bodies.clone(),
+                components: clone_components(&self.components),
+            }
+        }
+
+        /// Overwrites the state of this world with `snap`, leaving its settings in place.
+        ///
+        /// Entity ids, generations and the free list are rolled back too, so ids handed out after
+        /// the snapshot are handed out again and [`Entity`] handles taken after it may resolve to
+        /// the new entities.
+        pub fn restore(&mut self, snap: &WorldSnapshot) {
+            let snap = snap.clone();
+            self.positions = snap.positions;
+            self.velocities = snap.velocities;
+            self.accelerations = snap.accelerations;
+            self.entities = snap.entities;
+            self.indices = snap.indices;
+            self.proximity_warnings = snap.proximity_warnings;
+            self.next_entity = snap.next_entity;
+            self.generations = snap.generations;
+            self.free_ids = snap.free_ids;
+            self.epoch_seconds = snap.epoch_seconds;
+            self.gravity_bodies = snap.gravity_bodies;
+            self.components = snap.components;
+        }
+
+        /// Returns the delta-v (m/s) the entity has spent so far, or `None` if it does not exist.
+        pub fn total_delta_v(&self, entity: EntityId) -> Option<f64> {
+            self.index_of(entity)?;
+            Some(self.get_component::<DeltaVBudget>(entity).map_or(0.0, |b| b.0))
+        }
+
+        /// Adds `dv` (m/s) to the entity's [`DeltaVBudget`], attaching one if needed.
+        pub(crate) fn record_delta_v(&mut self, entity: EntityId, dv: f64) {
+            if let Some(budget) = self.get_component_mut::<DeltaVBudget>(entity) {
+                budget.0 += dv;
+            } else {
+                self.insert_component(entity, DeltaVBudget(dv));
+            }
+        }
+
+        /// Registers a component type so entities can carry it. Registering twice is a no-op.
+        ///
+        /// Components must be `Clone` so that [`World::snapshot`] can copy them.
+        pub fn register_component<T: Clone + Send + Sync + 'static>(&mut self) {
+            self.components
+                .entry(TypeId::of::<T>())
+                .or_insert_with(|| Box::new(HashMap::<EntityId, T>::new()));
+        }
+
+        /// Attaches a component to a live entity, registering the type if needed.
+        ///
+        /// Returns `false` without storing anything if the entity does not exist.
+        pub fn insert_component<T: Clone + Send + Sync + 'static>(
+            &mut self,
+            entity: EntityId,
+            value: T,
+        ) -> bool {
+            if self.index_of(entity).is_none() {
+                return false;
+            }
+            self.register_component::<T>();
+            self.components_mut::<T>().unwrap().insert(entity, value);
+            true
+        }
+
+        /// Detaches a component from an entity, returning it if it was present.
+        pub fn remove_component<T: Send + Sync + 'static>(
+            &mut self,
+            entity: EntityId,
+        ) -> Option<T> {
+            self.components_mut::<T>()?.remove(&entity)
+        }
+
+        pub fn get_component<T: Send + Sync + 'static>(&self, entity: EntityId) -> Option<&T> {
+            self.components::<T>()?.get(&entity)
+        }
+
+        pub fn get_component_mut<T: Send + Sync + 'static>(
+            &mut self,
+            entity: EntityId,
+        ) -> Option<&mut T> {
+            self.components_mut::<T>()?.get_mut(&entity)
+        }
+
+        /// Returns every stored `T` keyed by entity id, or `None` if `T` was never registered.
+        pub fn components<T: Send + Sync + 'static>(&self) -> Option<&HashMap<EntityId, T>> {
+            storage(&self.components)
+        }
+
+        pub fn components_mut<T: Send + Sync + 'static>(
+            &mut self,
+        ) -> Option<&mut HashMap<EntityId, T>> {
+            self.components
+                .get_mut(&TypeId::of::<T>())
+                .and_then(|s| s.as_any_mut().downcast_mut())
+        }
+
+        /// Sets a callback that the proximity detection systems call once for every warning,
+        /// in the order the warnings are returned. Replaces any previous callback.
+        ///
+        /// The callback must be `Send` because the world can be shared across threads.
+        pub fn set_proximity_callback(&mut self, f: ProximityCallback) {
+            *self.proximity_callback.get_mut().unwrap_or_else(|e| e.into_inner()) = Some(f);
+        }
+
+        /// Removes the proximity callback, if any.
+        pub fn clear_proximity_callback(&mut self) {
+            *self.proximity_callback.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+
+        /// Moves the simulation clock forward by `dt` seconds.
+        pub fn advance_time(&mut self, dt: f64) {
+            self.epoch_seconds += dt;
+        }
+
+        /// Registers a gravitating body, e.g. the Moon at its current position.
+        ///
+        /// Once any body is registered, [`gravity_system`] ignores its `gravitational_parameter`
+        /// argument and sums the pull of the registered bodies instead, so register the primary too.
+        pub fn add_gravity_body(&mut self, body: GravityBody) {
+            self.gravity_bodies.push(body);
+        }
+
+        /// Appends a force system to the list run by [`World::step_all`].
+        pub fn add_force_system(&mut self, system: Box<dyn ForceSystem>) {
+            self.force_systems.push(system);
+        }
+
+        /// Removes every registered force system.
+        pub fn clear_force_systems(&mut self) {
+            self.force_systems.clear();
+        }
+
+        /// Advances the world by one step: runs every registered force system in the order they
+        /// were added, then [`propagate_system`], then advances the clock.
+        pub fn step_all(&mut self, dt: f64) {
+            // Detach the list so each system can borrow the world mutably.
+            let mut systems = std::mem::take(&mut self.force_systems);
+            for system in &systems {
+                system.apply(self, dt);
+            }
+            systems.append(&mut self.force_systems);
+            self.force_systems = systems;
+            propagate_system(self, dt);
+            self.advance_time(dt);
+        }
+
+        /// Number of leading entries that have a position, a velocity and an entity id.
+        ///
+        /// The vectors always match when the world is only changed through its methods, but
+        /// they are public, so the proximity systems skip any unpaired tail instead of panicking.
+        fn paired_len(&self) -> usize {
+            self.positions.len().min(self.velocities.len()).min(self.entities.len())
+        }
+
+        /// Passes each warning to the proximity callback, if one is set.
+        fn notify_proximity(&self, warnings: &[ProximityWarning]) {
+            let mut callback = self.proximity_callback.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(f) = callback.as_mut() {
+                warnings.iter().for_each(f);
+            }
+        }
+    }
+
+    /// A force model that [`World::step_all`] runs each step, such as [`GravityForce`].
+    ///
+    /// Implementors should update velocities (or accumulate accelerations) but leave positions
+    /// alone; `step_all` propagates positions once all forces have run.
+    pub trait ForceSystem: Send + Sync {
+        fn apply(&self, world: &mut World, dt: f64);
+    }
+
+    /// Runs [`gravity_system`] with gravitational parameter `mu`.
+    #[derive(Debug, Clone)]
+    pub struct GravityForce {
+        pub mu: f64,
+    }
+
+    impl ForceSystem for GravityForce {
+        fn apply(&self, world: &mut World, dt: f64) {
+            gravity_system(world, dt, self.mu);
+        }
+    }
+
+    /// Runs [`j2_perturbation_system`].
+    #[derive(Debug, Clone)]
+    pub struct J2Force {
+        pub mu: f64,
+        pub j2: f64,
+        pub r_eq: f64,
+    }
+
+    impl ForceSystem for J2Force {
+        fn apply(&self, world: &mut World, dt: f64) {
+            j2_perturbation_system(world, dt, self.mu, self.j2, self.r_eq);
+        }
+    }
+
+    /// Runs [`drag_system`] with an exponential atmosphere.
+    #[derive(Debug, Clone)]
+    pub struct DragForce {
+        pub rho0: f64,
+        pub h0: f64,
+        pub scale_height: f64,
+    }
+
+    impl ForceSystem for DragForce {
+        fn apply(&self, world: &mut World, dt: f64) {
+            drag_system(world, dt, self.rho0, self.h0, self.scale_height);
+        }
+    }
+
+    /// Applies one acceleration per entity (indexed like the component vectors, `None` to skip).
+    ///
+    /// Velocities are kicked by v += a * dt, unless [`World::accumulate_forces`] is set, in which
+    /// case the accelerations are added to [`World::accelerations`] for [`integrate_system`].
+    fn apply_accelerations(world: &mut World, dt: f64, accelerations: Vec<Option<[f64; 3]>>) {
+        if world.accumulate_forces {
+            world.accelerations
+                .par_iter_mut()
+                .zip(accelerations.par_iter())
+                .for_each(|(acc, accel)| {
+                    if let Some(accel) = accel {
+                        acc.ax += accel[0];
+                        acc.ay += accel[1];
+                        acc.az += accel[2];
+                    }
+                });
+        } else {
+            world.velocities
+                .par_iter_mut()
+                .zip(accelerations.par_iter())
+                .for_each(|(vel, accel)| {
+                    if let Some(accel) = accel {
+                        vel.dx += accel[0] * dt;
+                        vel.dy += accel[1] * dt;
+                        vel.dz += accel[2] * dt;
+                    }
+                });
+        }
+    }
+
+    /// The integration system applies the accelerations summed by the force systems:
+    /// v += a * dt, then resets every [`Acceleration`] to zero.
+    pub fn integrate_system(world: &mut World, dt: f64) {
+        world.velocities
+            .par_iter_mut()
+            .zip(world.accelerations.par_iter_mut())
+            .for_each(|(vel, acc)| {
+                vel.dx += acc.ax * dt;
+                vel.dy += acc.ay * dt;
+                vel.dz += acc.az * dt;
+                *acc = Acceleration::default();
+            });
     }
 
     /// The gravity system updates velocities based on Earth's gravitational pull.
     ///
     /// It uses Euler integration: v += a * dt, where acceleration
     /// a = -μ * (r / |r|³), with μ being Earth's gravitational parameter.
+    /// If the world has [`GravityBody`]s registered, the accelerations towards all of them are
+    /// summed instead of using the primary at the origin.
+    /// Entities with a [`CentralBody`] are pulled towards that body alone using its μ,
+    /// with r measured from the body's position.
     pub fn gravity_system(world: &mut World, dt: f64, gravitational_parameter: f64) {
-        world.positions
+        let central_bodies = storage::<CentralBody>(&world.components);
+        let gravity_bodies = &world.gravity_bodies;
+        let accelerations = world.positions
             .par_iter()
-            .zip(world.velocities.par_iter_mut())
-            .for_each(|(pos, vel)| {
-                let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
-                if r > 0.0 {
-                    let accel_factor = -gravitational_parameter / (r * r * r);
-                    vel.dx += accel_factor * pos.x * dt;
-                    vel.dy += accel_factor * pos.y * dt;
-                    vel.dz += accel_factor * pos.z * dt;
+            .zip(world.entities.par_iter())
+            .map(|(pos, entity)| {
+                let (mu, r) = match central_bodies.and_then(|bodies| bodies.get(entity)) {
+                    Some(body) => (
+                        body.mu,
+                        [pos.x - body.position.x, pos.y - body.position.y, pos.z - body.position.z],
+                    ),
+                    None if !gravity_bodies.is_empty() => {
+                        return Some(gravity_bodies.iter().fold([0.0; 3], |a, body| {
+                            let r = [
+                                pos.x - body.position.x,
+                                pos.y - body.position.y,
+                                pos.z - body.position.z,
+                            ];
+                            add_scaled(a, 1.0, central_acceleration(r, body.mu))
+                        }));
+                    }
+                    None => (gravitational_parameter, [pos.x, pos.y, pos.z]),
+                };
+                Some(central_acceleration(r, mu))
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// Softening length (m) added to pairwise distances in [`nbody_gravity_system`],
+    /// so that coincident bodies don't produce infinite accelerations.
+    pub const NBODY_SOFTENING: f64 = 1.0;
+
+    /// The N-body gravity system updates velocities from the mutual attraction of massive entities.
+    ///
+    /// Every entity with a [`Mass`] is pulled towards every other one with
+    /// a = G * Σ m_j * d / (|d|² + ε²)^(3/2), where d points from the body to body j
+    /// and ε is [`NBODY_SOFTENING`]. Entities without a mass are neither pulled nor pulling.
+    pub fn nbody_gravity_system(world: &mut World, dt: f64, g: f64) {
+        let Some(masses) = storage::<Mass>(&world.components) else {
+            return;
+        };
+        let bodies: Vec<(usize, &Position, f64)> = world.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| masses.get(e).map(|m| (i, &world.positions[i], m.0)))
+            .collect();
+
+        let body_accelerations: Vec<(usize, [f64; 3])> = bodies
+            .par_iter()
+            .map(|&(i, pos, _)| {
+                let mut accel = [0.0; 3];
+                for &(j, other, mass) in &bodies {
+                    if i == j {
+                        continue;
+                    }
+                    let dx = other.x - pos.x;
+                    let dy = other.y - pos.y;
+                    let dz = other.z - pos.z;
+                    let d2 = dx * dx + dy * dy + dz * dz + NBODY_SOFTENING * NBODY_SOFTENING;
+                    let factor = g * mass / (d2 * d2.sqrt());
+                    accel[0] += factor * dx;
+                    accel[1] += factor * dy;
+                    accel[2] += factor * dz;
                 }
-            });
+                (i, accel)
+            })
+            .collect();
+
+        let mut accelerations = vec![None; world.positions.len()];
+        for (i, accel) in body_accelerations {
+            accelerations[i] = Some(accel);
+        }
+        apply_accelerations(world, dt, accelerations);
     }
 
     /// The propagation system updates positions based on their velocities.
     /// new_position = old_position + velocity * dt
+    ///
+    /// Afterwards the world's [`BoundaryMode`] is applied to every axis. Non-finite coordinates
+    /// are left as they are.
     pub fn propagate_system(world: &mut World, dt: f64) {
         world.positions
             .par_iter_mut()
@@ -85,46 +1165,2172 @@ pub mod ecs {
                 pos.y += vel.dy * dt;
                 pos.z += vel.dz * dt;
             });
-    }
 
-    /// The proximity detection system checks for any two satellites that are within a specified threshold.
-    ///
-    /// If the distance between any two satellites is less than `threshold` (in meters),
-    /// it prints a warning.
-    pub fn proximity_detection_system(world: &World, threshold: f64) -> HashSet<usize> {
-        let positions = &world.positions;
-        let len = positions.len();
-        let warning_mutex = Mutex::new(HashSet::new());
-        
-        (0..len).into_par_iter().for_each(|i| {
-            let mut local_warnings = HashSet::new();
-            for j in (i + 1)..len {
-                let pos1 = &positions[i];
-                let pos2 = &positions[j];
-                let dx = pos1.x - pos2.x;
-                let dy = pos1.y - pos2.y;
-                let dz = pos1.z - pos2.z;
-                let distance = (dx * dx + dy * dy + dz * dz).sqrt();
-                if distance < threshold {
-                    println!(
-                        "Warning: Satellites {} and {} are within {:.2} m (distance = {:.2} m)",
-                        i, j, threshold, distance
-                    );
-                    
-                    // Store locally
-                    local_warnings.insert(i);
-                    local_warnings.insert(j);
-                }
-            }
-            
-            // After processing, merge local warnings
-            if !local_warnings.is_empty() {
-                let mut global_warnings = warning_mutex.lock().unwrap();
-                global_warnings.extend(local_warnings);
-            }
-        });
-        
-        // Return the warnings
-        warning_mutex.into_inner().unwrap()
+        let (apply, half_width): (fn(&mut f64, &mut f64, f64), f64) = match world.boundary {
+            BoundaryMode::None => return,
+            BoundaryMode::Reflect(half_width) => (reflect, half_width),
+            BoundaryMode::Wrap(half_width) => (wrap, half_width),
+        };
+        world.positions
+            .par_iter_mut()
+            .zip(world.velocities.par_iter_mut())
+            .for_each(|(pos, vel)| {
+                apply(&mut pos.x, &mut vel.dx, half_width);
+                apply(&mut pos.y, &mut vel.dy, half_width);
+                apply(&mut pos.z, &mut vel.dz, half_width);
+            });
+    }
+
+    /// Folds `x` back into [-l, l] as if it had bounced between the walls, reversing `v` once
+    /// per bounce.
+    fn reflect(x: &mut f64, v: &mut f64, l: f64) {
+        if !x.is_finite() || (-l..=l).contains(x) {
+            return;
+        }
+        // Unfold the bounces: every 2l of travel past -l is one more wall crossing.
+        let shifted = *x + l;
+        let crossings = (shifted / (2.0 * l)).floor();
+        let y = shifted - 2.0 * l * crossings;
+        if crossings.rem_euclid(2.0) == 1.0 {
+            *x = l - y;
+            *v = -*v;
+        } else {
+            *x = y - l;
+        }
+    }
+
+    /// Moves `x` into [-l, l) by whole periods of 2l.
+    fn wrap(x: &mut f64, _v: &mut f64, l: f64) {
+        if x.is_finite() && !(-l..l).contains(x) {
+            *x = (*x + l).rem_euclid(2.0 * l) - l;
+        }
+    }
+
+    /// Earth's second zonal harmonic coefficient (dimensionless).
+    pub const EARTH_J2: f64 = 1.08263e-3;
+
+    /// Earth's equatorial radius in meters (WGS-84).
+    pub const EARTH_EQUATORIAL_RADIUS: f64 = 6.378137e6;
+
+    /// The J2 perturbation system adds the acceleration caused by the central body's equatorial bulge.
+    ///
+    /// With k = -3/2 * J2 * μ * r_eq² / r⁵:
+    /// aₓ = k * x * (1 - 5z²/r²), a_y = k * y * (1 - 5z²/r²), a_z = k * z * (3 - 5z²/r²).
+    /// For Earth use [`EARTH_J2`] and [`EARTH_EQUATORIAL_RADIUS`].
+    pub fn j2_perturbation_system(world: &mut World, dt: f64, mu: f64, j2: f64, r_eq: f64) {
+        let accelerations = world.positions
+            .par_iter()
+            .map(|pos| {
+                let r2 = pos.x * pos.x + pos.y * pos.y + pos.z * pos.z;
+                if r2 > 0.0 {
+                    let r = r2.sqrt();
+                    let k = -1.5 * j2 * mu * r_eq * r_eq / (r2 * r2 * r);
+                    let z2_r2 = pos.z * pos.z / r2;
+                    Some([
+                        k * pos.x * (1.0 - 5.0 * z2_r2),
+                        k * pos.y * (1.0 - 5.0 * z2_r2),
+                        k * pos.z * (3.0 - 5.0 * z2_r2),
+                    ])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// The drag system slows entities with [`DragProperties`] as they move through the atmosphere.
+    ///
+    /// Applies a = -½ * ρ * Cd * A / m * |v| * v, with an exponential atmosphere
+    /// ρ = ρ₀ * exp(-(h - h₀) / H) where h is the radius minus [`EARTH_EQUATORIAL_RADIUS`].
+    /// `rho0` is in kg/m³, `h0` and `scale_height` in meters.
+    pub fn drag_system(world: &mut World, dt: f64, rho0: f64, h0: f64, scale_height: f64) {
+        let Some(drag) = storage::<DragProperties>(&world.components) else {
+            return;
+        };
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .map(|((pos, vel), entity)| {
+                let props = drag.get(entity)?;
+                let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+                let altitude = r - EARTH_EQUATORIAL_RADIUS;
+                let rho = rho0 * (-(altitude - h0) / scale_height).exp();
+                let speed = (vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz).sqrt();
+                let accel_factor = -0.5 * rho * props.cd * props.area / props.mass * speed;
+                Some([accel_factor * vel.dx, accel_factor * vel.dy, accel_factor * vel.dz])
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// Solar radiation pressure at 1 AU in N/m².
+    pub const SOLAR_RADIATION_PRESSURE: f64 = 4.56e-6;
+
+    /// Cylindrical shadow model: the position is eclipsed when it lies on the anti-sun side
+    /// of Earth and within `earth_radius` of the Earth-Sun axis. `sun_direction` must be a unit
+    /// vector pointing from Earth to the Sun.
+    pub fn is_in_shadow(pos: &Position, sun_direction: [f64; 3], earth_radius: f64) -> bool {
+        let along = pos.x * sun_direction[0] + pos.y * sun_direction[1] + pos.z * sun_direction[2];
+        if along >= 0.0 {
+            return false;
+        }
+        let r2 = pos.x * pos.x + pos.y * pos.y + pos.z * pos.z;
+        r2 - along * along < earth_radius * earth_radius
+    }
+
+    /// The eclipse system lists the entities inside Earth's cylindrical shadow, ordered by id.
+    /// `sun_direction` points from Earth to the Sun and need not be normalized.
+    pub fn eclipse_system(world: &World, sun_direction: [f64; 3], earth_radius: f64) -> Vec<EntityId> {
+        let norm = (sun_direction[0] * sun_direction[0]
+            + sun_direction[1] * sun_direction[1]
+            + sun_direction[2] * sun_direction[2])
+            .sqrt();
+        if norm == 0.0 {
+            return Vec::new();
+        }
+        let sun = [sun_direction[0] / norm, sun_direction[1] / norm, sun_direction[2] / norm];
+        let mut shadowed: Vec<EntityId> = world.positions
+            .par_iter()
+            .zip(world.entities.par_iter())
+            .filter(|(pos, _)| is_in_shadow(pos, sun, earth_radius))
+            .map(|(_, &id)| id)
+            .collect();
+        shadowed.sort_unstable();
+        shadowed
+    }
+
+    /// The solar radiation pressure system pushes sunlit entities away from the Sun.
+    ///
+    /// Entities with both [`Reflectivity`] and [`DragProperties`] receive
+    /// a = P * Cr * A / m along the Sun-to-satellite direction, which for a distant Sun is
+    /// `-sun_direction`. `sun_direction` points from Earth to the Sun and need not be normalized.
+    ///
+    /// With `shadow_radius` set (e.g. to [`EARTH_EQUATORIAL_RADIUS`]), entities inside the
+    /// cylindrical shadow of that radius receive nothing, see [`is_in_shadow`]. With `None`
+    /// every entity is treated as sunlit.
+    pub fn srp_system(world: &mut World, dt: f64, sun_direction: [f64; 3], p_srp: f64, shadow_radius: Option<f64>) {
+        let (Some(reflectivity), Some(drag)) = (
+            storage::<Reflectivity>(&world.components),
+            storage::<DragProperties>(&world.components),
+        ) else {
+            return;
+        };
+        let norm = (sun_direction[0] * sun_direction[0]
+            + sun_direction[1] * sun_direction[1]
+            + sun_direction[2] * sun_direction[2])
+            .sqrt();
+        if norm == 0.0 {
+            return;
+        }
+        let sun = [sun_direction[0] / norm, sun_direction[1] / norm, sun_direction[2] / norm];
+
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.entities.par_iter())
+            .map(|(pos, entity)| {
+                let (cr, props) = (reflectivity.get(entity)?, drag.get(entity)?);
+                if shadow_radius.is_some_and(|radius| is_in_shadow(pos, sun, radius)) {
+                    return None;
+                }
+                let accel = p_srp * cr.0 * props.area / props.mass;
+                Some([-accel * sun[0], -accel * sun[1], -accel * sun[2]])
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// The third-body system adds the tidal pull of a distant body such as the Moon or the Sun.
+    ///
+    /// Applies the differential acceleration a = μ₃ * (d / |d|³ - s / |s|³), where d points from
+    /// the satellite to the body and s from Earth (the origin) to the body. Call it once per
+    /// perturbing body each step.
+    pub fn third_body_system(
+        world: &mut World,
+        dt: f64,
+        third_body_pos: [f64; 3],
+        third_body_mu: f64,
+    ) {
+        let s = third_body_pos;
+        let s_mag = (s[0] * s[0] + s[1] * s[1] + s[2] * s[2]).sqrt();
+        if s_mag == 0.0 {
+            return;
+        }
+        let s_factor = third_body_mu / (s_mag * s_mag * s_mag);
+
+        let accelerations = world.positions
+            .par_iter()
+            .map(|pos| {
+                let d = [s[0] - pos.x, s[1] - pos.y, s[2] - pos.z];
+                let d_mag = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+                if d_mag > 0.0 {
+                    let d_factor = third_body_mu / (d_mag * d_mag * d_mag);
+                    Some([
+                        d_factor * d[0] - s_factor * s[0],
+                        d_factor * d[1] - s_factor * s[1],
+                        d_factor * d[2] - s_factor * s[2],
+                    ])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// Speed of light in vacuum (m/s).
+    pub const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+    /// The relativistic correction system adds the first post-Newtonian (Schwarzschild) term of
+    /// the central body's gravity, from the IERS Conventions:
+    ///
+    /// a = μ / (c² r³) * ((4μ/r - v²) r + 4 (r · v) v)
+    ///
+    /// It advances the argument of perigee by 6πμ / (c² a (1 - e²)) per orbit, a few arcseconds a
+    /// century in low Earth orbit, so it is opt-in and only worth running for GPS-class precision.
+    /// `c` is normally [`SPEED_OF_LIGHT`].
+    pub fn relativistic_correction_system(world: &mut World, dt: f64, mu: f64, c: f64) {
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .map(|(pos, vel)| {
+                let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+                if r == 0.0 {
+                    return None;
+                }
+                let v2 = vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz;
+                let r_dot_v = pos.x * vel.dx + pos.y * vel.dy + pos.z * vel.dz;
+                let k = mu / (c * c * r * r * r);
+                let radial = 4.0 * mu / r - v2;
+                let along = 4.0 * r_dot_v;
+                Some([
+                    k * (radial * pos.x + along * vel.dx),
+                    k * (radial * pos.y + along * vel.dy),
+                    k * (radial * pos.z + along * vel.dz),
+                ])
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// The thrust system applies the acceleration of every enabled [`Thruster`].
+    ///
+    /// The direction is resolved from the current state each step, so a prograde thruster keeps
+    /// tracking the velocity as the orbit turns. Entities whose thrust direction is undefined
+    /// (a zero velocity, position or fixed vector) are skipped. The delta-v of each burn is
+    /// added to the entity's [`DeltaVBudget`].
+    pub fn thrust_system(world: &mut World, dt: f64) {
+        let Some(thrusters) = storage::<Thruster>(&world.components) else {
+            return;
+        };
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .map(|((pos, vel), entity)| {
+                let thruster = thrusters.get(entity).filter(|t| t.enabled)?;
+                thrust_acceleration(thruster.direction, thruster.acceleration, pos, vel)
+            })
+            .collect();
+        apply_thrust(world, dt, accelerations);
+    }
+
+    /// The scheduled thrust system applies the burn of every [`BurnSchedule`] segment active at
+    /// `epoch` (normally [`World::epoch_seconds`]), resolving its direction like [`thrust_system`].
+    ///
+    /// If several segments of a schedule overlap, only the first active one fires. The delta-v
+    /// of each burn is added to the entity's [`DeltaVBudget`].
+    pub fn scheduled_thrust_system(world: &mut World, epoch: f64, dt: f64) {
+        let Some(schedules) = storage::<BurnSchedule>(&world.components) else {
+            return;
+        };
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .map(|((pos, vel), entity)| {
+                let segment = schedules
+                    .get(entity)?
+                    .segments
+                    .iter()
+                    .find(|s| s.start <= epoch && epoch < s.end)?;
+                thrust_acceleration(segment.direction, segment.accel, pos, vel)
+            })
+            .collect();
+        apply_thrust(world, dt, accelerations);
+    }
+
+    /// Acceleration of magnitude `accel` along `direction`, or `None` if the direction is undefined.
+    fn thrust_acceleration(direction: ThrustFrame, accel: f64, pos: &Position, vel: &Velocity) -> Option<[f64; 3]> {
+        let dir = match direction {
+            ThrustFrame::Prograde => [vel.dx, vel.dy, vel.dz],
+            ThrustFrame::Radial => [pos.x, pos.y, pos.z],
+            ThrustFrame::Fixed(dir) => dir,
+        };
+        let norm = (dir[0] * dir[0] + dir[1] * dir[1] + dir[2] * dir[2]).sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        let k = accel / norm;
+        Some([dir[0] * k, dir[1] * k, dir[2] * k])
+    }
+
+    /// [`apply_accelerations`] that also charges each burn to the entity's [`DeltaVBudget`].
+    fn apply_thrust(world: &mut World, dt: f64, accelerations: Vec<Option<[f64; 3]>>) {
+        let spent: Vec<(EntityId, f64)> = accelerations
+            .iter()
+            .zip(world.entities.iter())
+            .filter_map(|(a, &entity)| {
+                let a = (*a)?;
+                Some((entity, (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt() * dt.abs()))
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+        for (entity, dv) in spent {
+            world.record_delta_v(entity, dv);
+        }
+    }
+
+    /// The formation system steers every entity with a [`FormationTarget`] towards its slot
+    /// at the leader's position plus the offset.
+    ///
+    /// Applies a = k * (r_leader + offset - r) - 2√k * (v - v_leader), a proportional pull with
+    /// gain `gain` (1/s²) plus critical damping on the relative velocity, so followers settle
+    /// into place instead of oscillating around it. Entities whose leader has been removed are
+    /// left alone.
+    pub fn formation_system(world: &mut World, gain: f64, dt: f64) {
+        let Some(targets) = storage::<FormationTarget>(&world.components) else {
+            return;
+        };
+        let damping = 2.0 * gain.sqrt();
+        let accelerations = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .map(|((pos, vel), entity)| {
+                let target = targets.get(entity)?;
+                let leader = world.component_index(target.leader)?;
+                let (lp, lv) = (&world.positions[leader], &world.velocities[leader]);
+                Some([
+                    gain * (lp.x + target.offset[0] - pos.x) - damping * (vel.dx - lv.dx),
+                    gain * (lp.y + target.offset[1] - pos.y) - damping * (vel.dy - lv.dy),
+                    gain * (lp.z + target.offset[2] - pos.z) - damping * (vel.dz - lv.dz),
+                ])
+            })
+            .collect();
+        apply_accelerations(world, dt, accelerations);
+    }
+
+    /// Acceleration towards a point mass at the origin: a = -μ * r / |r|³.
+    pub(crate) fn central_acceleration(r: [f64; 3], mu: f64) -> [f64; 3] {
+        let r_mag = (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt();
+        if r_mag > 0.0 {
+            let accel_factor = -mu / (r_mag * r_mag * r_mag);
+            [accel_factor * r[0], accel_factor * r[1], accel_factor * r[2]]
+        } else {
+            [0.0; 3]
+        }
+    }
+
+    /// Returns a + s * b.
+    fn add_scaled(a: [f64; 3], s: f64, b: [f64; 3]) -> [f64; 3] {
+        [a[0] + s * b[0], a[1] + s * b[1], a[2] + s * b[2]]
+    }
+
+    /// Advances one position/velocity pair by `dt` under central gravity using classic RK4.
+    pub(crate) fn rk4_central(r0: [f64; 3], v0: [f64; 3], dt: f64, mu: f64) -> ([f64; 3], [f64; 3]) {
+        let half = 0.5 * dt;
+
+        let k1_r = v0;
+        let k1_v = central_acceleration(r0, mu);
+        let k2_r = add_scaled(v0, half, k1_v);
+        let k2_v = central_acceleration(add_scaled(r0, half, k1_r), mu);
+        let k3_r = add_scaled(v0, half, k2_v);
+        let k3_v = central_acceleration(add_scaled(r0, half, k2_r), mu);
+        let k4_r = add_scaled(v0, dt, k3_v);
+        let k4_v = central_acceleration(add_scaled(r0, dt, k3_r), mu);
+
+        let mut r = r0;
+        let mut v = v0;
+        for k in 0..3 {
+            r[k] += dt / 6.0 * (k1_r[k] + 2.0 * k2_r[k] + 2.0 * k3_r[k] + k4_r[k]);
+            v[k] += dt / 6.0 * (k1_v[k] + 2.0 * k2_v[k] + 2.0 * k3_v[k] + k4_v[k]);
+        }
+        (r, v)
+    }
+
+    /// Advances positions and velocities together with fourth-order Runge-Kutta under central gravity.
+    ///
+    /// This replaces a `gravity_system` + `propagate_system` pair and drifts far less
+    /// over long runs than their first-order Euler update.
+    pub fn rk4_step(world: &mut World, dt: f64, mu: f64) {
+        world.positions
+            .par_iter_mut()
+            .zip(world.velocities.par_iter_mut())
+            .for_each(|(pos, vel)| {
+                let (r, v) = rk4_central([pos.x, pos.y, pos.z], [vel.dx, vel.dy, vel.dz], dt, mu);
+                *pos = Position { x: r[0], y: r[1], z: r[2] };
+                *vel = Velocity { dx: v[0], dy: v[1], dz: v[2] };
+            });
+    }
+
+    /// [`rk4_step`] that first rejects a non-positive `mu` or a non-finite `dt`.
+    pub fn rk4_step_checked(world: &mut World, dt: f64, mu: f64) -> Result<(), PhysicsError> {
+        check_mu(mu)?;
+        check_dt(dt)?;
+        rk4_step(world, dt, mu);
+        Ok(())
+    }
+
+    /// Advances positions and velocities with kick-drift-kick leapfrog (velocity Verlet) under central gravity.
+    ///
+    /// The scheme is symplectic, so orbital energy oscillates around its initial value
+    /// instead of drifting, which makes it the better choice for long propagations.
+    pub fn leapfrog_step(world: &mut World, dt: f64, mu: f64) {
+        world.positions
+            .par_iter_mut()
+            .zip(world.velocities.par_iter_mut())
+            .for_each(|(pos, vel)| {
+                let (r, v) = leapfrog_central([pos.x, pos.y, pos.z], [vel.dx, vel.dy, vel.dz], dt, mu);
+                *pos = Position { x: r[0], y: r[1], z: r[2] };
+                *vel = Velocity { dx: v[0], dy: v[1], dz: v[2] };
+            });
+    }
+
+    /// Advances one position/velocity pair by `dt` under central gravity with kick-drift-kick leapfrog.
+    pub(crate) fn leapfrog_central(r0: [f64; 3], v0: [f64; 3], dt: f64, mu: f64) -> ([f64; 3], [f64; 3]) {
+        let half = 0.5 * dt;
+        let v = add_scaled(v0, half, central_acceleration(r0, mu));
+        let r = add_scaled(r0, dt, v);
+        let v = add_scaled(v, half, central_acceleration(r, mu));
+        (r, v)
+    }
+
+    /// [`leapfrog_step`] that first rejects a non-positive `mu` or a non-finite `dt`.
+    pub fn leapfrog_step_checked(world: &mut World, dt: f64, mu: f64) -> Result<(), PhysicsError> {
+        check_mu(mu)?;
+        check_dt(dt)?;
+        leapfrog_step(world, dt, mu);
+        Ok(())
+    }
+
+    /// Give up shrinking the time step in [`adaptive_step`] after this many halvings.
+    const MAX_STEP_HALVINGS: u32 = 30;
+
+    /// Advances the world by one adaptively sized RK4 step under central gravity and returns the
+    /// time step actually taken.
+    ///
+    /// The error is estimated by step doubling: each entity is advanced once by `dt` and twice by
+    /// `dt / 2`, and the local position error is |r_half - r_full| / 15. Starting from `dt_max`,
+    /// the step is halved until the largest error across all entities is at most `tol` meters.
+    /// The more accurate two-half-step result is kept.
+    pub fn adaptive_step(world: &mut World, dt_max: f64, mu: f64, tol: f64) -> f64 {
+        let mut dt = dt_max;
+        let mut halvings = 0;
+        loop {
+            let trial: Vec<([f64; 3], [f64; 3], f64)> = world.positions
+                .par_iter()
+                .zip(world.velocities.par_iter())
+                .map(|(pos, vel)| {
+                    let r0 = [pos.x, pos.y, pos.z];
+                    let v0 = [vel.dx, vel.dy, vel.dz];
+                    let (r_full, _) = rk4_central(r0, v0, dt, mu);
+                    let (r_mid, v_mid) = rk4_central(r0, v0, 0.5 * dt, mu);
+                    let (r, v) = rk4_central(r_mid, v_mid, 0.5 * dt, mu);
+                    let dr = [r[0] - r_full[0], r[1] - r_full[1], r[2] - r_full[2]];
+                    let error = (dr[0] * dr[0] + dr[1] * dr[1] + dr[2] * dr[2]).sqrt() / 15.0;
+                    (r, v, error)
+                })
+                .collect();
+
+            let max_error = trial.iter().map(|t| t.2).fold(0.0, f64::max);
+            if max_error <= tol || halvings == MAX_STEP_HALVINGS {
+                let states = world.positions.iter_mut().zip(&mut world.velocities);
+                for ((pos, vel), (r, v, _)) in states.zip(trial) {
+                    *pos = Position { x: r[0], y: r[1], z: r[2] };
+                    *vel = Velocity { dx: v[0], dy: v[1], dz: v[2] };
+                }
+                return dt;
+            }
+            dt *= 0.5;
+            halvings += 1;
+        }
+    }
+
+    /// [`adaptive_step`] that first rejects a non-positive `mu` or a non-finite `dt_max`.
+    pub fn adaptive_step_checked(world: &mut World, dt_max: f64, mu: f64, tol: f64) -> Result<f64, PhysicsError> {
+        check_mu(mu)?;
+        check_dt(dt_max)?;
+        Ok(adaptive_step(world, dt_max, mu, tol))
+    }
+
+    /// The revolution system counts completed orbits for entities with a [`RevolutionCount`].
+    ///
+    /// Call it once per step. It compares each entity's true anomaly with the one seen by the
+    /// previous call and increments the count when the anomaly wraps from near 2π back to near 0,
+    /// i.e. when the entity passes periapsis. The first call only records the anomaly.
+    /// The step must be shorter than half an orbit for every passage to be seen.
+    pub fn revolution_system(world: &mut World, mu: f64) {
+        let Some(counts) = storage::<RevolutionCount>(&world.components) else {
+            return;
+        };
+        let anomalies: Vec<(EntityId, f64)> = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .filter(|(_, entity)| counts.contains_key(entity))
+            .map(|((pos, vel), &entity)| (entity, crate::orbit::state_to_elements(pos, vel, mu).nu))
+            .collect();
+
+        world.register_component::<PreviousAnomaly>();
+        for (entity, nu) in anomalies {
+            let previous = world.components_mut::<PreviousAnomaly>()
+                .unwrap()
+                .insert(entity, PreviousAnomaly(nu));
+            if matches!(previous, Some(PreviousAnomaly(prev)) if prev - nu > std::f64::consts::PI) {
+                world.get_component_mut::<RevolutionCount>(entity).unwrap().0 += 1;
+            }
+        }
+    }
+
+    /// A pair of entities found closer together than the proximity threshold.
+    ///
+    /// `a` is always the smaller entity id so that warnings for the same pair compare equal.
+    #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct ProximityWarning {
+        pub a: EntityId,
+        pub b: EntityId,
+        pub distance: f64,
+        /// Magnitude of the velocity difference at detection time (m/s). A fast crossing is
+        /// more dangerous than a slow drift at the same distance.
+        pub relative_speed: f64,
+    }
+
+    impl ProximityWarning {
+        /// Builds a warning for the entities at storage indices `i` and `j`, given in either order.
+        fn new(world: &World, i: usize, j: usize, distance: f64) -> Self {
+            let (e1, e2) = (world.entities[i], world.entities[j]);
+            let (v1, v2) = (&world.velocities[i], &world.velocities[j]);
+            let relative_speed = speed(&Velocity { dx: v1.dx - v2.dx, dy: v1.dy - v2.dy, dz: v1.dz - v2.dz });
+            Self { a: e1.min(e2), b: e1.max(e2), distance, relative_speed }
+        }
+    }
+
+    /// The proximity detection system checks for any two satellites that are within a specified threshold.
+    ///
+    /// Returns one warning per pair whose distance is less than `threshold` (in meters),
+    /// ordered by `a` then `b`.
+    pub fn proximity_detection_system(world: &World, threshold: f64) -> Vec<ProximityWarning> {
+        let positions = &world.positions;
+        let len = world.paired_len();
+
+        let mut warnings: Vec<ProximityWarning> = (0..len)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let pos1 = &positions[i];
+                ((i + 1)..len).filter_map(move |j| {
+                    let pos2 = &positions[j];
+                    let dx = pos1.x - pos2.x;
+                    let dy = pos1.y - pos2.y;
+                    let dz = pos1.z - pos2.z;
+                    let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+                    (distance < threshold).then(|| ProximityWarning::new(world, i, j, distance))
+                })
+            })
+            .collect();
+        warnings.sort_unstable_by_key(|w| (w.a, w.b));
+        world.notify_proximity(&warnings);
+        warnings
+    }
+
+    /// Grid cell containing a position, for cells `cell_size` meters on a side, or `None` if a
+    /// coordinate is not finite.
+    ///
+    /// Coordinates too far out for an `i64` cell index saturate to the outermost cell. That keeps
+    /// nearby positions in the same or neighbouring cells, so no pair within range is lost.
+    fn grid_cell(pos: &Position, cell_size: f64) -> Option<(i64, i64, i64)> {
+        if !(pos.x.is_finite() && pos.y.is_finite() && pos.z.is_finite()) {
+            return None;
+        }
+        Some((
+            (pos.x / cell_size).floor() as i64,
+            (pos.y / cell_size).floor() as i64,
+            (pos.z / cell_size).floor() as i64,
+        ))
+    }
+
+    /// Buckets entity indices into a uniform grid of `cell_size`-sized cells. Entities with a
+    /// non-finite position are left out: they are never within range of anything.
+    fn build_grid(positions: &[Position], cell_size: f64) -> HashMap<(i64, i64, i64), Vec<usize>> {
+        let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+        for (i, pos) in positions.iter().enumerate() {
+            if let Some(cell) = grid_cell(pos, cell_size) {
+                grid.entry(cell).or_default().push(i);
+            }
+        }
+        grid
+    }
+
+    /// Pushes a warning for every entity after index `i` in its own or a neighbouring grid cell
+    /// that is closer than `threshold`.
+    fn grid_warnings_for(
+        world: &World,
+        grid: &HashMap<(i64, i64, i64), Vec<usize>>,
+        threshold: f64,
+        i: usize,
+        warnings: &mut Vec<ProximityWarning>,
+    ) {
+        let positions = &world.positions;
+        let pos1 = &positions[i];
+        let Some((cx, cy, cz)) = grid_cell(pos1, threshold) else {
+            return;
+        };
+        for nx in cx.saturating_sub(1)..=cx.saturating_add(1) {
+            for ny in cy.saturating_sub(1)..=cy.saturating_add(1) {
+                for nz in cz.saturating_sub(1)..=cz.saturating_add(1) {
+                    let Some(cell) = grid.get(&(nx, ny, nz)) else {
+                        continue;
+                    };
+                    for &j in cell.iter().filter(|&&j| j > i) {
+                        let pos2 = &positions[j];
+                        let dx = pos1.x - pos2.x;
+                        let dy = pos1.y - pos2.y;
+                        let dz = pos1.z - pos2.z;
+                        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+                        if distance < threshold {
+                            warnings.push(ProximityWarning::new(world, i, j, distance));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as [`proximity_detection_system`], but buckets entities into a uniform grid of
+    /// `threshold`-sized cells so each entity is only compared against its own and the
+    /// 26 neighbouring cells. Returns identical warnings in the same order.
+    pub fn proximity_detection_grid(world: &World, threshold: f64) -> Vec<ProximityWarning> {
+        let warnings = grid_warnings(world, threshold);
+        world.notify_proximity(&warnings);
+        warnings
+    }
+
+    /// [`proximity_detection_grid`] without invoking the proximity callback.
+    fn grid_warnings(world: &World, threshold: f64) -> Vec<ProximityWarning> {
+        if threshold.is_nan() || threshold <= 0.0 {
+            return Vec::new();
+        }
+        let len = world.paired_len();
+        let grid = build_grid(&world.positions[..len], threshold);
+
+        let mut warnings = Vec::new();
+        for i in 0..len {
+            grid_warnings_for(world, &grid, threshold, i, &mut warnings);
+        }
+        warnings.sort_unstable_by_key(|w| (w.a, w.b));
+        warnings
+    }
+
+    /// [`ProximityWarning`] ordered by distance, then `a`, then `b`, for the bounded heap in
+    /// [`proximity_detection_limited`].
+    struct ByDistance(ProximityWarning);
+
+    impl ByDistance {
+        fn key(&self) -> (EntityId, EntityId) {
+            (self.0.a, self.0.b)
+        }
+    }
+
+    impl PartialEq for ByDistance {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for ByDistance {}
+
+    impl PartialOrd for ByDistance {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for ByDistance {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.distance
+                .total_cmp(&other.0.distance)
+                .then_with(|| self.key().cmp(&other.key()))
+        }
+    }
+
+    /// [`proximity_detection_grid`] that keeps at most `max_warnings` warnings: the closest
+    /// pairs, sorted by ascending distance with ties broken by `a`, then `b`.
+    ///
+    /// The closest pairs are kept in a bounded heap while scanning, so memory stays proportional
+    /// to `max_warnings` however many pairs are within `threshold`. Only the kept warnings are
+    /// passed to the proximity callback.
+    pub fn proximity_detection_limited(world: &World, threshold: f64, max_warnings: usize) -> Vec<ProximityWarning> {
+        if threshold.is_nan() || threshold <= 0.0 || max_warnings == 0 {
+            return Vec::new();
+        }
+        let len = world.paired_len();
+        let grid = build_grid(&world.positions[..len], threshold);
+
+        let mut closest: BinaryHeap<ByDistance> = BinaryHeap::with_capacity(max_warnings + 1);
+        let mut found = Vec::new();
+        for i in 0..len {
+            grid_warnings_for(world, &grid, threshold, i, &mut found);
+            for warning in found.drain(..) {
+                closest.push(ByDistance(warning));
+                if closest.len() > max_warnings {
+                    closest.pop();
+                }
+            }
+        }
+        let warnings: Vec<ProximityWarning> = closest
+            .into_sorted_vec()
+            .into_iter()
+            .map(|w| w.0)
+            .collect();
+        world.notify_proximity(&warnings);
+        warnings
+    }
+
+    /// Multi-threaded [`proximity_detection_grid`].
+    ///
+    /// Entities are split across the rayon thread pool, each thread collects warnings into its
+    /// own vector, and the vectors are merged and sorted at the end, so the result matches
+    /// [`proximity_detection_system`].
+    #[cfg(feature = "rayon")]
+    pub fn proximity_detection_parallel(world: &World, threshold: f64) -> Vec<ProximityWarning> {
+        if threshold.is_nan() || threshold <= 0.0 {
+            return Vec::new();
+        }
+        let len = world.paired_len();
+        let grid = build_grid(&world.positions[..len], threshold);
+
+        let mut warnings = (0..len)
+            .into_par_iter()
+            .fold(Vec::new, |mut warnings, i| {
+                grid_warnings_for(world, &grid, threshold, i, &mut warnings);
+                warnings
+            })
+            .reduce(Vec::new, |mut merged, mut warnings| {
+                merged.append(&mut warnings);
+                merged
+            });
+        warnings.par_sort_unstable_by_key(|w| (w.a, w.b));
+        world.notify_proximity(&warnings);
+        warnings
+    }
+
+    /// A proximity grid kept across steps, for when entities move little per step.
+    ///
+    /// [`PersistentGrid::update`] copies the current states but only moves the entities that
+    /// crossed into a different cell since the last update, instead of rebuilding every cell as
+    /// [`proximity_detection_grid`] does.
+    #[derive(Debug, Clone)]
+    pub struct PersistentGrid {
+        cell_size: f64,
+        cells: HashMap<(i64, i64, i64), Vec<EntityId>>,
+        entries: HashMap<EntityId, GridEntry>,
+    }
+
+    /// Cell and state of one entity as of the last [`PersistentGrid::update`].
+    #[derive(Debug, Clone)]
+    struct GridEntry {
+        cell: (i64, i64, i64),
+        position: Position,
+        velocity: Velocity,
+    }
+
+    impl PersistentGrid {
+        /// Creates an empty grid of `cell_size`-sized cells. Queries are cheapest with a cell
+        /// size equal to the threshold they use most.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `cell_size` is not a finite positive number.
+        pub fn new(cell_size: f64) -> Self {
+            assert!(
+                cell_size.is_finite() && cell_size > 0.0,
+                "cell size must be a finite positive number, got {cell_size}"
+            );
+            Self { cell_size, cells: HashMap::new(), entries: HashMap::new() }
+        }
+
+        /// Brings the grid up to date with `world`: new entities are inserted, removed ones are
+        /// dropped, and entities that changed cell are moved. Entities with a non-finite position
+        /// are left out until they have a finite one again.
+        pub fn update(&mut self, world: &World) {
+            let len = world.paired_len();
+            let live: HashSet<EntityId> = world.entities[..len].iter().copied().collect();
+            let gone: Vec<EntityId> = self.entries.keys().filter(|id| !live.contains(id)).copied().collect();
+            for id in gone {
+                let entry = self.entries.remove(&id).unwrap();
+                self.remove_from_cell(entry.cell, id);
+            }
+
+            for i in 0..len {
+                let id = world.entities[i];
+                let position = world.positions[i].clone();
+                let velocity = world.velocities[i].clone();
+                let Some(cell) = grid_cell(&position, self.cell_size) else {
+                    if let Some(entry) = self.entries.remove(&id) {
+                        self.remove_from_cell(entry.cell, id);
+                    }
+                    continue;
+                };
+                let previous = self.entries.insert(id, GridEntry { cell, position, velocity });
+                match previous {
+                    Some(entry) if entry.cell == cell => {}
+                    Some(entry) => {
+                        self.remove_from_cell(entry.cell, id);
+                        self.cells.entry(cell).or_default().push(id);
+                    }
+                    None => self.cells.entry(cell).or_default().push(id),
+                }
+            }
+        }
+
+        fn remove_from_cell(&mut self, cell: (i64, i64, i64), id: EntityId) {
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                if let Some(k) = ids.iter().position(|&other| other == id) {
+                    ids.swap_remove(k);
+                }
+                if ids.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+
+        /// Returns one warning per pair closer than `threshold` as of the last update, ordered
+        /// by `a` then `b`, the same warnings [`proximity_detection_grid`] gives for that state.
+        /// Returns nothing if `threshold` is not a finite positive number. Does not invoke the
+        /// world's proximity callback.
+        ///
+        /// A threshold much larger than the cell size spans many cells, so past the number of
+        /// occupied cells the query compares the occupied cells with each other instead of
+        /// probing every cell in range.
+        pub fn query_pairs(&self, threshold: f64) -> Vec<ProximityWarning> {
+            if !threshold.is_finite() || threshold <= 0.0 {
+                return Vec::new();
+            }
+            // Cells a pair can be apart and still be within the threshold.
+            let reach = (threshold / self.cell_size).ceil();
+            let span = 2.0 * reach + 1.0;
+            let walk_occupied = span * span * span > self.cells.len() as f64;
+            let reach = reach as u64;
+
+            let mut warnings = Vec::new();
+            for (&cell, ids) in &self.cells {
+                let neighbors: Vec<&Vec<EntityId>> = if walk_occupied {
+                    self.cells
+                        .iter()
+                        .filter(|(other, _)| {
+                            cell.0.abs_diff(other.0) <= reach
+                                && cell.1.abs_diff(other.1) <= reach
+                                && cell.2.abs_diff(other.2) <= reach
+                        })
+                        .map(|(_, ids)| ids)
+                        .collect()
+                } else {
+                    let r = reach as i64;
+                    let offset = |d: (i64, i64, i64)| {
+                        Some((cell.0.checked_add(d.0)?, cell.1.checked_add(d.1)?, cell.2.checked_add(d.2)?))
+                    };
+                    (-r..=r)
+                        .flat_map(|dx| (-r..=r).flat_map(move |dy| (-r..=r).map(move |dz| (dx, dy, dz))))
+                        .filter_map(|d| self.cells.get(&offset(d)?))
+                        .collect()
+                };
+                for &a in ids {
+                    let e1 = &self.entries[&a];
+                    for &b in neighbors.iter().flat_map(|ids| ids.iter()).filter(|&&b| b > a) {
+                        let e2 = &self.entries[&b];
+                        let (p1, p2) = (&e1.position, &e2.position);
+                        let dx = p1.x - p2.x;
+                        let dy = p1.y - p2.y;
+                        let dz = p1.z - p2.z;
+                        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+                        if distance < threshold {
+                            let (v1, v2) = (&e1.velocity, &e2.velocity);
+                            let relative_speed = speed(&Velocity {
+                                dx: v1.dx - v2.dx,
+                                dy: v1.dy - v2.dy,
+                                dz: v1.dz - v2.dz,
+                            });
+                            warnings.push(ProximityWarning { a, b, distance, relative_speed });
+                        }
+                    }
+                }
+            }
+            warnings.sort_unstable_by_key(|w| (w.a, w.b));
+            warnings
+        }
+    }
+
+    /// The reentry system removes every entity whose altitude (radius minus `earth_radius`) is
+    /// below `reentry_altitude`, returning the removed ids in ascending order.
+    pub fn reentry_system(world: &mut World, earth_radius: f64, reentry_altitude: f64) -> Vec<EntityId> {
+        let mut reentered: Vec<EntityId> = world.positions
+            .par_iter()
+            .zip(world.entities.par_iter())
+            .filter(|(pos, _)| {
+                let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+                r - earth_radius < reentry_altitude
+            })
+            .map(|(_, &entity)| entity)
+            .collect();
+        reentered.sort_unstable();
+        for &entity in &reentered {
+            world.remove_entity(entity);
+        }
+        reentered
+    }
+
+    /// The surface collision system removes every entity that has sunk below the surface of a
+    /// spherical central body of radius `earth_radius` (e.g. [`EARTH_EQUATORIAL_RADIUS`]),
+    /// returning the removed ids in ascending order.
+    ///
+    /// This is the hard floor under [`reentry_system`]: it catches entities that passed through
+    /// the atmosphere within a single step, or worlds that run without a reentry check.
+    pub fn surface_collision_system(world: &mut World, earth_radius: f64) -> Vec<EntityId> {
+        reentry_system(world, earth_radius, 0.0)
+    }
+
+    /// The escape cleanup system removes every entity that is farther than `max_radius` from
+    /// the origin or no longer bound to the central body (specific energy v²/2 - μ/r ≥ 0),
+    /// returning the removed ids in ascending order.
+    pub fn escape_cleanup_system(world: &mut World, max_radius: f64, mu: f64) -> Vec<EntityId> {
+        let mut escaped: Vec<EntityId> = world.positions
+            .par_iter()
+            .zip(world.velocities.par_iter())
+            .zip(world.entities.par_iter())
+            .filter(|((pos, vel), _)| {
+                let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+                let v2 = vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz;
+                r > max_radius || v2 / 2.0 - mu / r >= 0.0
+            })
+            .map(|(_, &entity)| entity)
+            .collect();
+        escaped.sort_unstable();
+        for &entity in &escaped {
+            world.remove_entity(entity);
+        }
+        escaped
+    }
+
+    /// The collision system destroys every pair of entities closer than `collision_radius`.
+    ///
+    /// All colliding pairs are found before anything is removed, so an entity that hits
+    /// several others shows up in each of those pairs. Returns the destroyed pairs.
+    /// Collisions are not reported to the proximity callback.
+    pub fn collision_system(world: &mut World, collision_radius: f64) -> Vec<(EntityId, EntityId)> {
+        let pairs: Vec<(EntityId, EntityId)> = grid_warnings(world, collision_radius)
+            .into_iter()
+            .map(|w| (w.a, w.b))
+            .collect();
+        for &(a, b) in &pairs {
+            world.remove_entity(a);
+            world.remove_entity(b);
+        }
+        pairs
+    }
+
+    /// Largest speed (m/s) a fragment from [`fragmentation_system`] gains relative to the
+    /// center of mass of its collision.
+    pub const FRAGMENT_SPEED_SPREAD: f64 = 100.0;
+
+    /// The fragmentation system replaces every pair of entities closer than `collision_radius`
+    /// with `fragments_per_collision` debris fragments, returning the fragment ids. Fragments get
+    /// fresh ids, never those of the entities that broke up.
+    ///
+    /// Fragments start at the midpoint of the pair and move with the pair's center-of-mass
+    /// velocity (equal weights unless both carry [`Mass`]) plus a random kick in a uniformly random
+    /// direction of up to [`FRAGMENT_SPEED_SPREAD`]. They all start at the same point, so they
+    /// must be propagated apart before this system runs again. Pairs are handled in ascending
+    /// (a, b) order, and an entity that hits several others only breaks up in its first pair.
+    /// The same `seed` and world always produce the same fragments.
+    pub fn fragmentation_system(
+        world: &mut World,
+        collision_radius: f64,
+        fragments_per_collision: usize,
+        seed: u64,
+    ) -> Vec<EntityId> {
+        // Held by handle: fragments reuse the ids of the entities they replace, so a raw id in a
+        // later pair could otherwise name a fragment.
+        let pairs: Vec<(Entity, Entity)> = grid_warnings(world, collision_radius)
+            .into_iter()
+            .filter_map(|w| Some((world.entity(w.a)?, world.entity(w.b)?)))
+            .collect();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut fragments = Vec::new();
+        for (a, b) in pairs {
+            // Skips pairs whose entities already broke up with another partner.
+            let (Some(i), Some(j)) = (world.component_index(a), world.component_index(b)) else {
+                continue;
+            };
+            let (m1, m2) = match (world.get_component::<Mass>(a.id()), world.get_component::<Mass>(b.id())) {
+                (Some(&Mass(m1)), Some(&Mass(m2))) => (m1, m2),
+                _ => (1.0, 1.0),
+            };
+            let (p1, p2) = (&world.positions[i], &world.positions[j]);
+            let center = [(p1.x + p2.x) / 2.0, (p1.y + p2.y) / 2.0, (p1.z + p2.z) / 2.0];
+            let (v1, v2) = (&world.velocities[i], &world.velocities[j]);
+            let total = m1 + m2;
+            let velocity = [
+                (m1 * v1.dx + m2 * v2.dx) / total,
+                (m1 * v1.dy + m2 * v2.dy) / total,
+                (m1 * v1.dz + m2 * v2.dz) / total,
+            ];
+            world.remove_entity(a.id());
+            world.remove_entity(b.id());
+
+            for _ in 0..fragments_per_collision {
+                let speed = rng.gen_range(0.0..FRAGMENT_SPEED_SPREAD);
+                let theta = rng.gen_range(0.0..std::f64::consts::TAU);
+                let u: f64 = rng.gen_range(-1.0..1.0);
+                let sin_phi = (1.0 - u * u).sqrt();
+                let id = world.add_entity(
+                    Position { x: center[0], y: center[1], z: center[2] },
+                    Velocity {
+                        dx: velocity[0] + speed * sin_phi * theta.cos(),
+                        dy: velocity[1] + speed * sin_phi * theta.sin(),
+                        dz: velocity[2] + speed * u,
+                    },
+                );
+                fragments.push(id);
+            }
+        }
+        fragments
+    }
+
+    /// Bounces apart every approaching pair of entities with [`Mass`] closer than `collision_radius`.
+    ///
+    /// Each pair exchanges an impulse along the line of centers that reverses their closing speed
+    /// scaled by `restitution` (1 is perfectly elastic, 0 perfectly inelastic), conserving momentum.
+    /// Pairs that are already separating are left alone, so overlapping entities are not bounced
+    /// again on the next step. Pairs are resolved in ascending (a, b) order, and entities without
+    /// [`Mass`] pass through each other.
+    pub fn elastic_collision_system(world: &mut World, collision_radius: f64, restitution: f64) {
+        let pairs = grid_warnings(world, collision_radius);
+        let Some(mass) = storage::<Mass>(&world.components) else {
+            return;
+        };
+        for warning in pairs {
+            let (Some(&Mass(m1)), Some(&Mass(m2))) = (mass.get(&warning.a), mass.get(&warning.b)) else {
+                continue;
+            };
+            let (Some(i), Some(j)) = (world.index_of(warning.a), world.index_of(warning.b)) else {
+                continue;
+            };
+            let (p1, p2) = (&world.positions[i], &world.positions[j]);
+            let d = [p1.x - p2.x, p1.y - p2.y, p1.z - p2.z];
+            let distance = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+            if distance == 0.0 {
+                continue;
+            }
+            let n = [d[0] / distance, d[1] / distance, d[2] / distance];
+
+            let (v1, v2) = (&world.velocities[i], &world.velocities[j]);
+            let closing = (v1.dx - v2.dx) * n[0] + (v1.dy - v2.dy) * n[1] + (v1.dz - v2.dz) * n[2];
+            if closing >= 0.0 {
+                continue;
+            }
+            let impulse = -(1.0 + restitution) * closing / (1.0 / m1 + 1.0 / m2);
+
+            let v1 = &mut world.velocities[i];
+            v1.dx += impulse / m1 * n[0];
+            v1.dy += impulse / m1 * n[1];
+            v1.dz += impulse / m1 * n[2];
+            let v2 = &mut world.velocities[j];
+            v2.dx -= impulse / m2 * n[0];
+            v2.dy -= impulse / m2 * n[1];
+            v2.dz -= impulse / m2 * n[2];
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn at(x: f64, y: f64, z: f64) -> Position {
+            Position { x, y, z }
+        }
+
+        fn moving(dx: f64, dy: f64, dz: f64) -> Velocity {
+            Velocity { dx, dy, dz }
+        }
+
+        fn still() -> Velocity {
+            moving(0.0, 0.0, 0.0)
+        }
+
+        #[test]
+        fn removed_then_recreated_slot_rejects_old_handle() {
+            let mut world = World::new();
+            let first = world.add_entity(at(1.0, 0.0, 0.0), still());
+            let old = world.entity(first).unwrap();
+            world.insert_component(first, Mass(5.0));
+
+            assert!(world.remove_entity(first));
+            let second = world.add_entity(at(2.0, 0.0, 0.0), still());
+
+            // The new entity takes over both the storage slot and the id, but not the handle.
+            assert_eq!(second, first);
+            assert_eq!(world.index_of(second), Some(0));
+            assert!(!world.is_alive(old));
+            assert!(world.position(old).is_none());
+            assert!(world.velocity(old).is_none());
+            assert!(world.position_mut(old).is_none());
+            assert!(world.get_component::<Mass>(second).is_none());
+
+            let new = world.entity(second).unwrap();
+            assert_ne!(new, old);
+            assert_eq!(new.id(), old.id());
+            assert!(world.is_alive(new));
+            assert_eq!(world.position(new).unwrap().x, 2.0);
+        }
+
+        #[test]
+        fn despawn_all_invalidates_handles_and_renumbers_from_zero() {
+            let mut world = World::new();
+            let ids: Vec<EntityId> = (0..3).map(|i| world.add_entity(at(i as f64, 0.0, 0.0), still())).collect();
+            world.remove_entity(1);
+            let handles: Vec<Entity> = [0, 2].iter().map(|&id| world.entity(ids[id]).unwrap()).collect();
+
+            world.despawn_all();
+            let next: Vec<EntityId> = (0..4).map(|_| world.add_entity(at(0.0, 0.0, 0.0), still())).collect();
+
+            assert!(handles.iter().all(|&h| !world.is_alive(h)));
+            assert_eq!(next, vec![0, 1, 2, 3]);
+        }
+
+        /// A satellite behind Earth on the anti-sun side, with SRP properties attached.
+        fn srp_world() -> (World, EntityId) {
+            let mut world = World::new();
+            let id = world.add_entity(at(-7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.insert_component(id, Reflectivity(1.5));
+            world.insert_component(id, DragProperties { cd: 2.2, area: 10.0, mass: 100.0 });
+            (world, id)
+        }
+
+        #[test]
+        fn srp_leaves_eclipsed_satellite_unchanged() {
+            let (mut world, id) = srp_world();
+            srp_system(&mut world, 10.0, [1.0, 0.0, 0.0], SOLAR_RADIATION_PRESSURE, Some(EARTH_EQUATORIAL_RADIUS));
+
+            let vel = &world.velocities[world.index_of(id).unwrap()];
+            assert_eq!([vel.dx, vel.dy, vel.dz], [0.0, 7.5e3, 0.0]);
+        }
+
+        #[test]
+        fn srp_without_shadow_check_pushes_away_from_sun() {
+            let (mut world, id) = srp_world();
+            srp_system(&mut world, 10.0, [2.0, 0.0, 0.0], SOLAR_RADIATION_PRESSURE, None);
+
+            let vel = &world.velocities[world.index_of(id).unwrap()];
+            let expected = -SOLAR_RADIATION_PRESSURE * 1.5 * 10.0 / 100.0 * 10.0;
+            assert!((vel.dx - expected).abs() < 1e-18);
+            assert_eq!([vel.dy, vel.dz], [7.5e3, 0.0]);
+        }
+
+        /// `n` entities at uniformly random positions in a cube of half-width `half_width`.
+        fn random_world(n: usize, half_width: f64, seed: u64) -> World {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut world = World::new();
+            for _ in 0..n {
+                let pos = at(
+                    rng.gen_range(-half_width..half_width),
+                    rng.gen_range(-half_width..half_width),
+                    rng.gen_range(-half_width..half_width),
+                );
+                let vel = moving(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0), 0.0);
+                world.add_entity(pos, vel);
+            }
+            world
+        }
+
+        #[test]
+        fn grid_matches_brute_force_on_random_world() {
+            let world = random_world(500, 5.0e5, 16);
+            let brute = proximity_detection_system(&world, 5.0e4);
+            assert!(brute.len() > 10);
+            assert_eq!(proximity_detection_grid(&world, 5.0e4), brute);
+        }
+
+        #[test]
+        fn grid_skips_non_finite_positions() {
+            let mut world = random_world(50, 1.0e3, 17);
+            world.add_entity(at(f64::INFINITY, 0.0, 0.0), still());
+            world.add_entity(at(0.0, f64::NAN, 0.0), still());
+            world.add_entity(at(0.0, 0.0, f64::NEG_INFINITY), still());
+
+            let brute = proximity_detection_system(&world, 500.0);
+            assert!(!brute.is_empty());
+            assert_eq!(proximity_detection_grid(&world, 500.0), brute);
+        }
+
+        #[test]
+        fn grid_handles_cells_beyond_i64_range() {
+            let mut world = World::new();
+            world.add_entity(at(1.0e7, 0.0, 0.0), still());
+            world.add_entity(at(1.0e7, 0.0, 0.0), still());
+            world.add_entity(at(-1.0e7, 0.0, 0.0), still());
+
+            let warnings = proximity_detection_grid(&world, 1e-12);
+            assert_eq!(warnings, proximity_detection_system(&world, 1e-12));
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(collision_system(&mut world, 1e-12), vec![(0, 1)]);
+        }
+
+        /// Storage of `T` sorted by id, for comparing worlds.
+        #[cfg(feature = "serde")]
+        fn dump<T: std::fmt::Debug + Send + Sync + 'static>(world: &World) -> String {
+            let sorted = world.components::<T>().map(|m| m.iter().collect::<std::collections::BTreeMap<_, _>>());
+            format!("{sorted:?}")
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn json_round_trip_is_bit_identical() {
+            let mut world = random_world(12, 7.0e6, 20);
+            world.remove_entity(3);
+            world.remove_entity(8);
+            world.epoch_seconds = 1234.5;
+            world.accelerations[2].ax = 0.1 + 0.2;
+            world.add_gravity_body(GravityBody { mu: 3.986004418e14, position: at(0.0, 0.0, 0.0) });
+            let ids = world.entities_sorted();
+            assert_eq!(ids.len(), 10);
+            for (k, &id) in ids.iter().enumerate() {
+                let x = k as f64 / 3.0;
+                world.insert_component(id, Mass(100.0 + x));
+                world.insert_component(id, DeltaVBudget(x));
+                if k % 2 == 0 {
+                    world.insert_component(id, Label(format!("sat-{k}")));
+                    world.insert_component(id, DragProperties { cd: 2.2, area: x, mass: 50.0 });
+                    world.insert_component(id, Reflectivity(1.3));
+                    world.insert_component(id, RevolutionCount(k as u32));
+                    world.insert_component(id, PreviousAnomaly(x));
+                } else {
+                    world.insert_component(id, Thruster { direction: ThrustFrame::Fixed([x, 1.0, 0.0]), acceleration: 1e-3, enabled: true });
+                    world.insert_component(id, BurnSchedule {
+                        segments: vec![BurnSegment { start: 0.0, end: x, direction: ThrustFrame::Prograde, accel: 0.01 }],
+                    });
+                    world.insert_component(id, FormationTarget { leader: world.entity(ids[0]).unwrap(), offset: [x, -x, 0.5] });
+                    world.insert_component(id, CentralBody { mu: 4.9e12, position: at(x, 0.0, 3.8e8) });
+                }
+            }
+
+            let restored = World::from_json(&world.to_json().unwrap()).unwrap();
+
+            let bits = |w: &World| -> Vec<u64> {
+                w.positions.iter().flat_map(|p| [p.x, p.y, p.z])
+                    .chain(w.velocities.iter().flat_map(|v| [v.dx, v.dy, v.dz]))
+                    .chain(w.accelerations.iter().flat_map(|a| [a.ax, a.ay, a.az]))
+                    .map(f64::to_bits)
+                    .collect()
+            };
+            assert_eq!(bits(&restored), bits(&world));
+            assert_eq!(restored.entities, world.entities);
+            assert_eq!(restored.indices, world.indices);
+            assert_eq!(restored.generations, world.generations);
+            assert_eq!(restored.next_entity, world.next_entity);
+            assert_eq!(restored.epoch_seconds.to_bits(), world.epoch_seconds.to_bits());
+            assert_eq!(format!("{:?}", restored.gravity_bodies), format!("{:?}", world.gravity_bodies));
+            assert_eq!(dump::<Mass>(&restored), dump::<Mass>(&world));
+            assert_eq!(dump::<CentralBody>(&restored), dump::<CentralBody>(&world));
+            assert_eq!(dump::<DragProperties>(&restored), dump::<DragProperties>(&world));
+            assert_eq!(dump::<Reflectivity>(&restored), dump::<Reflectivity>(&world));
+            assert_eq!(dump::<RevolutionCount>(&restored), dump::<RevolutionCount>(&world));
+            assert_eq!(dump::<PreviousAnomaly>(&restored), dump::<PreviousAnomaly>(&world));
+            assert_eq!(dump::<Label>(&restored), dump::<Label>(&world));
+            assert_eq!(dump::<Thruster>(&restored), dump::<Thruster>(&world));
+            assert_eq!(dump::<BurnSchedule>(&restored), dump::<BurnSchedule>(&world));
+            assert_eq!(dump::<FormationTarget>(&restored), dump::<FormationTarget>(&world));
+            assert_eq!(dump::<DeltaVBudget>(&restored), dump::<DeltaVBudget>(&world));
+            assert_eq!(restored.components.len(), world.components.len());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn to_json_rejects_non_finite_state() {
+            let mut world = random_world(3, 1.0e3, 21);
+            assert!(World::from_json(&world.to_json().unwrap()).is_ok());
+
+            world.velocities[1].dy = f64::NAN;
+            assert!(world.to_json().is_err());
+            world.velocities[1].dy = 0.0;
+            world.accelerations[0].az = f64::INFINITY;
+            assert!(world.to_json().is_err());
+        }
+
+        #[test]
+        fn index_mapping_survives_removals() {
+            let mut world = World::new();
+            let ids: Vec<EntityId> = (0..10).map(|k| world.add_entity(at(k as f64, 0.0, 0.0), still())).collect();
+            for &id in &[0, 4, 9, 5] {
+                assert!(world.remove_entity(ids[id]));
+            }
+            let added = world.add_entity(at(42.0, 0.0, 0.0), still());
+            assert_eq!(added, 5, "the most recently freed id is reused first");
+
+            assert_eq!(world.positions.len(), 7);
+            assert_eq!(world.entities.len(), 7);
+            for (index, &id) in world.entities.iter().enumerate() {
+                assert_eq!(world.index_of(id), Some(index));
+                let expected = if id == added { 42.0 } else { id as f64 };
+                assert_eq!(world.positions[index].x, expected);
+            }
+            for &id in &[0, 4, 9] {
+                assert_eq!(world.index_of(ids[id]), None);
+            }
+            assert_eq!(world.entities_sorted(), vec![1, 2, 3, 5, 6, 7, 8]);
+        }
+
+        #[test]
+        fn add_remove_churn_keeps_id_tables_bounded() {
+            let mut world = random_world(100, 1.0e6, 28);
+            for round in 0..1000 {
+                let id = world.entities[round % world.entities.len()];
+                world.remove_entity(id);
+                world.add_entity(at(round as f64, 0.0, 0.0), still());
+            }
+            assert_eq!(world.entities.len(), 100);
+            assert_eq!(world.next_entity, 100);
+            assert_eq!((world.indices.len(), world.generations.len()), (100, 100));
+            assert!(world.free_ids.is_empty());
+        }
+
+        #[test]
+        fn formation_follower_converges_to_offset() {
+            let mut world = World::new();
+            let leader = world.add_entity(at(0.0, 0.0, 0.0), moving(1.0, 0.0, 0.0));
+            let follower = world.add_entity(at(500.0, 50.0, -20.0), still());
+            let leader = world.entity(leader).unwrap();
+            world.insert_component(follower, FormationTarget { leader, offset: [100.0, 0.0, 0.0] });
+
+            let error = |world: &World| {
+                let (l, f) = (&world.positions[0], &world.positions[1]);
+                let d = [l.x + 100.0 - f.x, l.y - f.y, l.z - f.z];
+                (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+            };
+            let initial = error(&world);
+            for _ in 0..2000 {
+                formation_system(&mut world, 1e-2, 1.0);
+                propagate_system(&mut world, 1.0);
+            }
+            assert!(error(&world) < 1e-3 * initial);
+        }
+
+        #[test]
+        fn formation_ignores_removed_leader() {
+            let mut world = World::new();
+            let leader = world.add_entity(at(0.0, 0.0, 0.0), still());
+            let follower = world.add_entity(at(500.0, 0.0, 0.0), moving(0.0, 1.0, 0.0));
+            let handle = world.entity(leader).unwrap();
+            world.insert_component(follower, FormationTarget { leader: handle, offset: [100.0, 0.0, 0.0] });
+
+            world.remove_entity(leader);
+            // Takes over the leader's id, but not its handle.
+            assert_eq!(world.add_entity(at(1.0e9, 0.0, 0.0), moving(1.0e6, 0.0, 0.0)), leader);
+            formation_system(&mut world, 1e-2, 1.0);
+
+            let vel = &world.velocities[world.index_of(follower).unwrap()];
+            assert_eq!([vel.dx, vel.dy, vel.dz], [0.0, 1.0, 0.0]);
+        }
+
+        #[test]
+        fn fragmentation_replaces_pair_with_fresh_fragments() {
+            let mut world = World::new();
+            let a = world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            let b = world.add_entity(at(7.0e6 + 5.0, 0.0, 0.0), moving(0.0, -7.5e3, 0.0));
+            let bystander = world.add_entity(at(-7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.insert_component(a, Mass(100.0));
+            world.insert_component(b, Mass(300.0));
+            let (a, b) = (world.entity(a).unwrap(), world.entity(b).unwrap());
+
+            let fragments = fragmentation_system(&mut world, 10.0, 4, 75);
+
+            assert_eq!(fragments.len(), 4);
+            assert_eq!(world.entities.len(), 3 - 2 + 4);
+            assert!(!world.is_alive(a) && !world.is_alive(b));
+            assert!(world.index_of(bystander).is_some());
+            assert!(!fragments.contains(&bystander));
+            // The first two fragments take over the freed ids.
+            assert_eq!(fragments[..2], [b.id(), a.id()]);
+            for &f in &fragments {
+                let i = world.index_of(f).unwrap();
+                let (pos, vel) = (&world.positions[i], &world.velocities[i]);
+                assert_eq!([pos.x, pos.y, pos.z], [7.0e6 + 2.5, 0.0, 0.0]);
+                // Center-of-mass velocity is (0, -3750, 0), kicked by at most the spread.
+                let kick = speed(&moving(vel.dx, vel.dy + 3750.0, vel.dz));
+                assert!(kick < FRAGMENT_SPEED_SPREAD);
+            }
+        }
+
+        #[test]
+        fn fragmentation_breaks_each_entity_up_once() {
+            let mut world = World::new();
+            for x in [0.0, 4.0, 8.0] {
+                world.add_entity(at(x, 0.0, 0.0), still());
+            }
+            // (0, 1) and (1, 2) both collide; 1 only breaks up with 0.
+            let fragments = fragmentation_system(&mut world, 5.0, 2, 1);
+            // The fragments reuse ids 1 and 0, which must not make (1, 2) collide again.
+            assert_eq!(fragments, vec![1, 0]);
+            assert_eq!(world.entities_sorted(), vec![0, 1, 2]);
+        }
+
+        #[test]
+        fn speed_of_three_four_zero_is_five() {
+            assert_eq!(speed(&moving(3.0, 4.0, 0.0)), 5.0);
+        }
+
+        #[test]
+        fn speeds_are_ordered_by_id() {
+            let mut world = World::new();
+            for v in [1.0, 2.0, 3.0] {
+                world.add_entity(at(0.0, 0.0, 0.0), moving(0.0, 0.0, v));
+            }
+            world.remove_entity(0);
+            assert_eq!(world.speeds(), vec![(1, 2.0), (2, 3.0)]);
+        }
+
+        #[test]
+        fn persistent_grid_matches_rebuilt_grid_after_steps() {
+            let mut world = random_world(300, 1.0e4, 89);
+            let mut grid = PersistentGrid::new(1.0e3);
+            let mut seen = 0;
+            for step in 0..20 {
+                if step == 5 {
+                    world.remove_entity(7);
+                    world.add_entity(at(0.0, 0.0, 0.0), moving(5.0, 5.0, 5.0));
+                }
+                propagate_system(&mut world, 30.0);
+                grid.update(&world);
+                let pairs = grid.query_pairs(1.0e3);
+                assert_eq!(pairs, proximity_detection_grid(&world, 1.0e3));
+                seen += pairs.len();
+            }
+            assert!(seen > 100);
+            assert_eq!(grid.query_pairs(2.5e3), proximity_detection_grid(&world, 2.5e3));
+        }
+
+        #[test]
+        fn persistent_grid_handles_large_and_non_finite_thresholds() {
+            let mut world = random_world(10, 1.0e3, 90);
+            world.add_entity(at(f64::NAN, 0.0, 0.0), still());
+            let mut grid = PersistentGrid::new(1.0);
+            grid.update(&world);
+
+            let all = grid.query_pairs(1.0e6);
+            assert_eq!(all.len(), 45);
+            assert_eq!(all, proximity_detection_system(&world, 1.0e6));
+            assert!(grid.query_pairs(f64::INFINITY).is_empty());
+            assert!(grid.query_pairs(f64::NAN).is_empty());
+        }
+
+        #[test]
+        fn pair_within_threshold_gives_one_warning() {
+            let mut world = World::new();
+            let a = world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            let b = world.add_entity(at(7.0e6, 5.0e3, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.add_entity(at(-7.0e6, 0.0, 0.0), still());
+
+            let warnings = proximity_detection_system(&world, 1.0e4);
+            assert_eq!(warnings.len(), 1);
+            assert_eq!((warnings[0].a, warnings[0].b), (a, b));
+            assert!((warnings[0].distance - 5.0e3).abs() < 1e-6);
+        }
+
+        #[test]
+        fn gravity_after_removal_skips_removed_entity() {
+            let mut world = World::new();
+            let a = world.add_entity(at(7.0e6, 0.0, 0.0), still());
+            let b = world.add_entity(at(0.0, 8.0e6, 0.0), still());
+            let c = world.add_entity(at(0.0, 0.0, 9.0e6), still());
+            world.insert_component(b, Mass(10.0));
+
+            assert!(world.remove_entity(b));
+            gravity_system(&mut world, 10.0, 3.986004418e14);
+
+            assert_eq!(world.entities.len(), 2);
+            assert_eq!(world.index_of(b), None);
+            assert!(world.get_component::<Mass>(b).is_none());
+            let va = &world.velocities[world.index_of(a).unwrap()];
+            let vc = &world.velocities[world.index_of(c).unwrap()];
+            assert!(va.dx < 0.0 && va.dy == 0.0);
+            assert!(vc.dz < 0.0 && vc.dy == 0.0);
+        }
+
+        #[test]
+        fn add_entity_stores_position_and_velocity_together() {
+            let mut world = World::new();
+            let first = world.add_entity(at(1.0, 2.0, 3.0), moving(4.0, 5.0, 6.0));
+            let second = world.add_entity(at(7.0, 8.0, 9.0), still());
+
+            assert_eq!((first, second), (0, 1));
+            let i = world.index_of(first).unwrap();
+            assert_eq!((world.positions[i].x, world.positions[i].z), (1.0, 3.0));
+            assert_eq!((world.velocities[i].dx, world.velocities[i].dz), (4.0, 6.0));
+            assert_eq!(world.positions.len(), world.velocities.len());
+            assert!(world.proximity_warnings.is_empty());
+        }
+
+        #[test]
+        fn custom_component_reads_back() {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Mass(f64);
+
+            let mut world = World::new();
+            let a = world.add_entity(at(0.0, 0.0, 0.0), still());
+            let b = world.add_entity(at(1.0, 0.0, 0.0), still());
+            world.register_component::<Mass>();
+            assert!(world.insert_component(a, Mass(250.0)));
+
+            assert_eq!(world.get_component::<Mass>(a), Some(&Mass(250.0)));
+            assert_eq!(world.get_component::<Mass>(b), None);
+            world.get_component_mut::<Mass>(a).unwrap().0 += 50.0;
+            assert_eq!(world.remove_component::<Mass>(a), Some(Mass(300.0)));
+            assert!(world.components::<Mass>().unwrap().is_empty());
+        }
+
+        #[test]
+        fn nbody_pair_kick_matches_newton() {
+            const G: f64 = 6.674e-11;
+            let mut world = World::new();
+            let heavy = world.add_entity(at(0.0, 0.0, 0.0), still());
+            let light = world.add_entity(at(1.0e6, 0.0, 0.0), still());
+            world.insert_component(heavy, Mass(1.0e20));
+            world.insert_component(light, Mass(1.0e3));
+
+            nbody_gravity_system(&mut world, 10.0, G);
+
+            let expected = G * 1.0e20 / (1.0e6 * 1.0e6) * 10.0;
+            let vel = &world.velocities[world.index_of(light).unwrap()];
+            assert!((vel.dx + expected).abs() < 1e-9 * expected);
+            assert_eq!([vel.dy, vel.dz], [0.0, 0.0]);
+            assert!(world.velocities[world.index_of(heavy).unwrap()].dx > 0.0);
+        }
+
+        const MU: f64 = 3.986004418e14;
+
+        /// Adds an entity on a circular equatorial orbit of radius `r` around [`MU`].
+        fn add_circular(world: &mut World, r: f64) -> EntityId {
+            world.add_entity(at(r, 0.0, 0.0), moving(0.0, (MU / r).sqrt(), 0.0))
+        }
+
+        fn radius(world: &World, id: EntityId) -> f64 {
+            let p = &world.positions[world.index_of(id).unwrap()];
+            (p.x * p.x + p.y * p.y + p.z * p.z).sqrt()
+        }
+
+        #[test]
+        fn rk4_keeps_circular_radius_over_one_period() {
+            let mut world = World::new();
+            let id = add_circular(&mut world, 7.0e6);
+            let period = crate::orbit::orbital_period(7.0e6, MU);
+            for _ in 0..600 {
+                rk4_step(&mut world, period / 600.0, MU);
+            }
+            assert!((radius(&world, id) / 7.0e6 - 1.0).abs() < 1e-3);
+        }
+
+        #[test]
+        fn leapfrog_bounds_semi_major_axis_over_1000_periods() {
+            let mut world = World::new();
+            let id = add_circular(&mut world, 7.0e6);
+            let period = crate::orbit::orbital_period(7.0e6, MU);
+            for _ in 0..1000 {
+                for _ in 0..100 {
+                    leapfrog_step(&mut world, period / 100.0, MU);
+                }
+                let i = world.index_of(id).unwrap();
+                let energy = crate::orbit::specific_orbital_energy(&world.positions[i], &world.velocities[i], MU);
+                let a = -MU / (2.0 * energy);
+                assert!((a / 7.0e6 - 1.0).abs() < 5e-3);
+            }
+        }
+
+        #[test]
+        fn central_body_mu_sets_acceleration() {
+            let mut world = World::new();
+            let moon_at = at(3.844e8, 0.0, 0.0);
+            let earth_sat = world.add_entity(at(7.0e6, 0.0, 0.0), still());
+            let moon_sat = world.add_entity(at(3.844e8 + 7.0e6, 0.0, 0.0), still());
+            world.insert_component(moon_sat, CentralBody { mu: 4.9048695e12, position: moon_at });
+
+            gravity_system(&mut world, 1.0, MU);
+
+            let dv = |id| world.velocities[world.index_of(id).unwrap()].dx;
+            let r2 = 7.0e6 * 7.0e6;
+            assert!((dv(earth_sat) + MU / r2).abs() < 1e-12);
+            assert!((dv(moon_sat) + 4.9048695e12 / r2).abs() < 1e-12);
+            assert!(dv(earth_sat) < 50.0 * dv(moon_sat));
+        }
+
+        #[test]
+        fn j2_regresses_node_of_prograde_near_polar_orbit() {
+            use crate::orbit::{elements_to_state, state_to_elements, OrbitalElements};
+            let start = OrbitalElements { a: 7.0e6, e: 0.001, i: 80f64.to_radians(), raan: 1.0, argp: 0.5, nu: 0.0 };
+            let (pos, vel) = elements_to_state(&start, MU);
+            let mut world = World::new();
+            world.add_entity(pos, vel);
+
+            // One day of J2 kicks split around leapfrog steps.
+            for _ in 0..8640 {
+                j2_perturbation_system(&mut world, 10.0, MU, EARTH_J2, EARTH_EQUATORIAL_RADIUS);
+                leapfrog_step(&mut world, 10.0, MU);
+            }
+
+            let raan = state_to_elements(&world.positions[0], &world.velocities[0], MU).raan;
+            // Secular rate -3/2 n J2 (R/a)² cos i is about -1.2°/day here.
+            let drift = (raan - 1.0).to_degrees();
+            assert!(drift < -0.8 && drift > -1.6, "drift {drift}°");
+        }
+
+        #[test]
+        fn drag_lowers_semi_major_axis_every_orbit() {
+            let mut world = World::new();
+            let id = add_circular(&mut world, EARTH_EQUATORIAL_RADIUS + 3.0e5);
+            world.insert_component(id, DragProperties { cd: 2.2, area: 10.0, mass: 100.0 });
+            let semi_major_axis = |world: &World| {
+                let energy = crate::orbit::specific_orbital_energy(&world.positions[0], &world.velocities[0], MU);
+                -MU / (2.0 * energy)
+            };
+
+            let mut previous = semi_major_axis(&world);
+            for _ in 0..10 {
+                for _ in 0..545 {
+                    drag_system(&mut world, 10.0, 1.9e-11, 3.0e5, 5.0e4);
+                    leapfrog_step(&mut world, 10.0, MU);
+                }
+                let a = semi_major_axis(&world);
+                assert!(a < previous);
+                previous = a;
+            }
+        }
+
+        #[test]
+        fn moon_gives_geo_satellite_tangential_acceleration() {
+            let mut world = World::new();
+            let id = add_circular(&mut world, 4.2164e7);
+            let (sin, cos) = 30f64.to_radians().sin_cos();
+            let moon = [3.844e8 * cos, 3.844e8 * sin, 0.0];
+
+            third_body_system(&mut world, 1.0, moon, 4.9048695e12);
+
+            let vel = &world.velocities[world.index_of(id).unwrap()];
+            let tangential = vel.dy - (MU / 4.2164e7).sqrt();
+            // The Moon sits ahead of the satellite, so it pulls it along its orbit.
+            assert!(tangential > 1e-7, "tangential {tangential}");
+            assert!(tangential < 1e-4);
+        }
+
+        #[cfg(feature = "rayon")]
+        #[test]
+        fn parallel_matches_brute_force_on_2000_satellites() {
+            let world = random_world(2000, 1.0e6, 17);
+            let brute = proximity_detection_system(&world, 5.0e4);
+            assert!(brute.len() > 100);
+            assert_eq!(proximity_detection_parallel(&world, 5.0e4), brute);
+        }
+
+        #[test]
+        fn collision_removes_both_satellites_one_meter_apart() {
+            let mut world = World::new();
+            let a = world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            let b = world.add_entity(at(7.0e6 + 1.0, 0.0, 0.0), moving(0.0, -7.5e3, 0.0));
+            let far = world.add_entity(at(-7.0e6, 0.0, 0.0), still());
+
+            assert_eq!(collision_system(&mut world, 10.0), vec![(a, b)]);
+            assert_eq!(world.entities_sorted(), vec![far]);
+        }
+
+        #[test]
+        fn iter_mobile_yields_only_entities_with_both_components() {
+            let mut world = World::new();
+            for k in 0..4 {
+                world.add_entity(at(k as f64, 0.0, 0.0), moving(0.0, k as f64, 0.0));
+            }
+            world.remove_entity(1);
+            // A position pushed without a velocity has no partner to join with.
+            world.positions.push(at(99.0, 0.0, 0.0));
+
+            let mut seen: Vec<EntityId> = world.iter_mobile()
+                .map(|(id, pos, vel)| {
+                    assert_eq!((pos.x, vel.dy), (id as f64, id as f64));
+                    id
+                })
+                .collect();
+            seen.sort_unstable();
+            assert_eq!(seen, vec![0, 2, 3]);
+
+            for (_, pos, vel) in world.iter_mobile_mut() {
+                pos.y = vel.dy;
+            }
+            assert_eq!(world.positions.iter().map(|p| p.y).collect::<Vec<_>>(), vec![0.0, 3.0, 2.0, 0.0]);
+        }
+
+        #[test]
+        fn parallel_step_matches_one_entity_at_a_time_bit_for_bit() {
+            let mut world = World::new();
+            let mut rng = StdRng::seed_from_u64(29);
+            for _ in 0..5000 {
+                let r = rng.gen_range(6.6e6..4.0e7);
+                let (sin, cos) = rng.gen_range(0.0..std::f64::consts::TAU).sin_cos();
+                let v = (MU / r).sqrt();
+                world.add_entity(at(r * cos, r * sin, rng.gen_range(-1.0e6..1.0e6)), moving(-v * sin, v * cos, 0.0));
+            }
+            let mut singles: Vec<World> = world.iter_mobile()
+                .map(|(_, pos, vel)| {
+                    let mut single = World::new();
+                    single.add_entity(pos.clone(), vel.clone());
+                    single
+                })
+                .collect();
+
+            for _ in 0..10 {
+                gravity_system(&mut world, 10.0, MU);
+                propagate_system(&mut world, 10.0);
+                for single in &mut singles {
+                    gravity_system(single, 10.0, MU);
+                    propagate_system(single, 10.0);
+                }
+            }
+
+            for (single, (_, pos, vel)) in singles.iter().zip(world.iter_mobile()) {
+                let (p, v) = (&single.positions[0], &single.velocities[0]);
+                assert_eq!([p.x, p.y, p.z].map(f64::to_bits), [pos.x, pos.y, pos.z].map(f64::to_bits));
+                assert_eq!([v.dx, v.dy, v.dz].map(f64::to_bits), [vel.dx, vel.dy, vel.dz].map(f64::to_bits));
+            }
+        }
+
+        #[test]
+        fn adaptive_step_is_shorter_at_perigee() {
+            use crate::orbit::{elements_to_state, OrbitalElements};
+            let step_at = |nu: f64| {
+                let elements = OrbitalElements { a: 2.0e7, e: 0.65, i: 0.3, raan: 0.0, argp: 0.0, nu };
+                let (pos, vel) = elements_to_state(&elements, MU);
+                let mut world = World::new();
+                world.add_entity(pos, vel);
+                adaptive_step(&mut world, 600.0, MU, 1e-3)
+            };
+
+            let (perigee, apogee) = (step_at(0.0), step_at(std::f64::consts::PI));
+            assert!(perigee < apogee, "perigee {perigee} s, apogee {apogee} s");
+        }
+
+        #[test]
+        fn accumulated_forces_add_up() {
+            let leo = || {
+                let mut world = World::new();
+                let id = add_circular(&mut world, EARTH_EQUATORIAL_RADIUS + 2.5e5);
+                world.insert_component(id, DragProperties { cd: 2.2, area: 4.0, mass: 50.0 });
+                world
+            };
+            let kick = |world: &World| {
+                let v = &world.velocities[0];
+                [v.dx, v.dy - (MU / (EARTH_EQUATORIAL_RADIUS + 2.5e5)).sqrt(), v.dz]
+            };
+            let (mut gravity, mut drag, mut both) = (leo(), leo(), leo());
+            gravity_system(&mut gravity, 10.0, MU);
+            drag_system(&mut drag, 10.0, 1.0e-10, 2.5e5, 5.0e4);
+            both.accumulate_forces = true;
+            gravity_system(&mut both, 10.0, MU);
+            drag_system(&mut both, 10.0, 1.0e-10, 2.5e5, 5.0e4);
+            assert_eq!(kick(&both), [0.0; 3]);
+            integrate_system(&mut both, 10.0);
+
+            let (g, d, total) = (kick(&gravity), kick(&drag), kick(&both));
+            assert!(d[1] < 0.0);
+            for k in 0..3 {
+                assert!((total[k] - (g[k] + d[k])).abs() < 1e-9, "axis {k}");
+            }
+            let acc = &both.accelerations[0];
+            assert_eq!([acc.ax, acc.ay, acc.az], [0.0; 3]);
+        }
+
+        #[test]
+        fn satellite_behind_earth_is_shadowed_and_beside_it_is_sunlit() {
+            let sun = [1.0, 0.0, 0.0];
+            let behind = at(-7.0e6, 0.0, 0.0);
+            let beside = at(-7.0e6, 7.0e6, 0.0);
+            let facing_sun = at(7.0e6, 0.0, 0.0);
+            assert!(is_in_shadow(&behind, sun, EARTH_EQUATORIAL_RADIUS));
+            assert!(!is_in_shadow(&beside, sun, EARTH_EQUATORIAL_RADIUS));
+            assert!(!is_in_shadow(&facing_sun, sun, EARTH_EQUATORIAL_RADIUS));
+
+            let mut world = World::new();
+            world.add_entity(beside, still());
+            let shadowed = world.add_entity(behind, still());
+            world.add_entity(facing_sun, still());
+            assert_eq!(eclipse_system(&world, sun, EARTH_EQUATORIAL_RADIUS), vec![shadowed]);
+        }
+
+        #[test]
+        fn prograde_thruster_raises_semi_major_axis_every_step() {
+            let mut world = World::new();
+            let id = add_circular(&mut world, 7.0e6);
+            world.insert_component(id, Thruster { direction: ThrustFrame::Prograde, acceleration: 1e-3, enabled: true });
+            let semi_major_axis = |world: &World| {
+                let energy = crate::orbit::specific_orbital_energy(&world.positions[0], &world.velocities[0], MU);
+                -MU / (2.0 * energy)
+            };
+
+            let mut previous = semi_major_axis(&world);
+            for _ in 0..1000 {
+                thrust_system(&mut world, 10.0);
+                rk4_step(&mut world, 10.0, MU);
+                let a = semi_major_axis(&world);
+                assert!(a > previous);
+                previous = a;
+            }
+            assert!(previous > 7.0e6 + 1.0e4);
+        }
+
+        #[test]
+        fn proximity_callback_sees_every_warning() {
+            use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+            use std::sync::Arc;
+
+            let mut world = random_world(200, 1.0e5, 40);
+            let count = Arc::new(AtomicUsize::new(0));
+            let counter = Arc::clone(&count);
+            world.set_proximity_callback(Box::new(move |_| {
+                counter.fetch_add(1, AtomicOrdering::Relaxed);
+            }));
+
+            let warnings = proximity_detection_system(&world, 2.0e4);
+            assert!(!warnings.is_empty());
+            assert_eq!(count.load(AtomicOrdering::Relaxed), warnings.len());
+
+            world.clear_proximity_callback();
+            assert_eq!(proximity_detection_grid(&world, 2.0e4), warnings);
+            assert_eq!(count.load(AtomicOrdering::Relaxed), warnings.len());
+        }
+
+        /// Force system that records its name when applied.
+        struct Recorder {
+            name: &'static str,
+            log: std::sync::Arc<Mutex<Vec<&'static str>>>,
+        }
+
+        impl ForceSystem for Recorder {
+            fn apply(&self, _world: &mut World, _dt: f64) {
+                self.log.lock().unwrap().push(self.name);
+            }
+        }
+
+        #[test]
+        fn force_systems_run_in_insertion_order() {
+            let log = std::sync::Arc::new(Mutex::new(Vec::new()));
+            let mut world = World::new();
+            world.add_entity(at(0.0, 0.0, 0.0), moving(1.0, 0.0, 0.0));
+            world.add_force_system(Box::new(Recorder { name: "second", log: log.clone() }));
+            world.add_force_system(Box::new(Recorder { name: "first", log: log.clone() }));
+
+            world.step_all(2.0);
+            world.step_all(2.0);
+
+            assert_eq!(*log.lock().unwrap(), vec!["second", "first", "second", "first"]);
+            assert_eq!(world.positions[0].x, 4.0);
+            assert_eq!(world.epoch_seconds, 4.0);
+        }
+
+        #[test]
+        fn proximity_skips_unpaired_tail() {
+            let mut world = World::new();
+            world.add_entity(at(0.0, 0.0, 0.0), still());
+            world.add_entity(at(10.0, 0.0, 0.0), still());
+            // A position with no velocity or entity id behind it, right next to both.
+            world.positions.push(at(5.0, 0.0, 0.0));
+
+            let expected = vec![ProximityWarning { a: 0, b: 1, distance: 10.0, relative_speed: 0.0 }];
+            assert_eq!(proximity_detection_system(&world, 20.0), expected);
+            assert_eq!(proximity_detection_grid(&world, 20.0), expected);
+            assert_eq!(proximity_detection_limited(&world, 20.0, 5), expected);
+        }
+
+        #[test]
+        fn symmetric_gravity_bodies_cancel_laterally() {
+            let mut world = World::new();
+            world.add_entity(at(0.0, 1.0e7, 0.0), still());
+            world.add_gravity_body(GravityBody { mu: MU, position: at(-3.844e8 / 2.0, 0.0, 0.0) });
+            world.add_gravity_body(GravityBody { mu: MU, position: at(3.844e8 / 2.0, 0.0, 0.0) });
+
+            gravity_system(&mut world, 10.0, 0.0);
+
+            let vel = &world.velocities[0];
+            assert_eq!(vel.dx, 0.0);
+            assert!(vel.dy < 0.0);
+            assert_eq!(vel.dz, 0.0);
+        }
+
+        #[test]
+        fn clock_reads_1000_after_100_steps_of_10() {
+            let mut world = World::new();
+            world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.add_force_system(Box::new(GravityForce { mu: MU }));
+            for _ in 0..100 {
+                world.step_all(10.0);
+            }
+            assert_eq!(world.epoch_seconds, 1000.0);
+
+            let mut manual = World::new();
+            for _ in 0..100 {
+                manual.advance_time(10.0);
+            }
+            assert_eq!(manual.epoch_seconds, 1000.0);
+        }
+
+        #[test]
+        fn revolution_count_increments_once_per_period() {
+            use crate::orbit::{elements_to_state, orbital_period, OrbitalElements};
+            let elements = OrbitalElements { a: 8.0e6, e: 0.1, i: 0.5, raan: 0.0, argp: 0.0, nu: 0.3 };
+            let (pos, vel) = elements_to_state(&elements, MU);
+            let mut world = World::new();
+            let id = world.add_entity(pos, vel);
+            world.insert_component(id, RevolutionCount(0));
+            let dt = orbital_period(elements.a, MU) / 500.0;
+
+            revolution_system(&mut world, MU);
+            for _ in 0..500 {
+                rk4_step(&mut world, dt, MU);
+                revolution_system(&mut world, MU);
+            }
+            assert_eq!(world.get_component::<RevolutionCount>(id).unwrap().0, 1);
+
+            for _ in 0..250 {
+                rk4_step(&mut world, dt, MU);
+                revolution_system(&mut world, MU);
+            }
+            assert_eq!(world.get_component::<RevolutionCount>(id).unwrap().0, 1);
+        }
+
+        #[test]
+        fn reentry_removes_and_reports_satellite_in_atmosphere() {
+            let mut world = World::new();
+            let high = add_circular(&mut world, EARTH_EQUATORIAL_RADIUS + 4.0e5);
+            let low = add_circular(&mut world, EARTH_EQUATORIAL_RADIUS + 8.0e4);
+
+            assert_eq!(reentry_system(&mut world, EARTH_EQUATORIAL_RADIUS, 1.2e5), vec![low]);
+            assert_eq!(world.entities_sorted(), vec![high]);
+            assert!(reentry_system(&mut world, EARTH_EQUATORIAL_RADIUS, 1.2e5).is_empty());
+        }
+
+        #[test]
+        fn escape_cleanup_removes_distant_and_hyperbolic_entities() {
+            let mut world = World::new();
+            let bound = add_circular(&mut world, 7.0e6);
+            let distant = world.add_entity(at(1.0e12, 0.0, 0.0), still());
+            let hyperbolic = world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 1.2e4, 0.0));
+
+            assert_eq!(escape_cleanup_system(&mut world, 1.0e9, MU), vec![distant, hyperbolic]);
+            assert_eq!(world.entities_sorted(), vec![bound]);
+        }
+
+        #[test]
+        fn warning_order_is_identical_across_runs() {
+            let run = || {
+                let mut world = random_world(400, 2.0e5, 71);
+                // Removals shuffle the storage order but not the output order.
+                for id in [3, 150, 7, 399] {
+                    world.remove_entity(id);
+                }
+                (proximity_detection_system(&world, 2.0e4), proximity_detection_grid(&world, 2.0e4))
+            };
+            let (brute, grid) = run();
+            assert!(brute.len() > 20);
+            assert!(brute.windows(2).all(|w| (w[0].a, w[0].b) < (w[1].a, w[1].b)));
+            assert_eq!(grid, brute);
+            for _ in 0..5 {
+                assert_eq!(run(), (brute.clone(), grid.clone()));
+            }
+        }
+
+        #[test]
+        fn equal_masses_swap_velocities_head_on() {
+            let mut world = World::new();
+            let a = world.add_entity(at(0.0, 0.0, 0.0), moving(3.0, 0.0, 0.0));
+            let b = world.add_entity(at(1.0, 0.0, 0.0), moving(-5.0, 0.0, 0.0));
+            world.insert_component(a, Mass(100.0));
+            world.insert_component(b, Mass(100.0));
+
+            elastic_collision_system(&mut world, 2.0, 1.0);
+
+            let (va, vb) = (&world.velocities[0], &world.velocities[1]);
+            assert!((va.dx + 5.0).abs() < 1e-12 && (vb.dx - 3.0).abs() < 1e-12);
+            assert_eq!([va.dy, va.dz, vb.dy, vb.dz], [0.0; 4]);
+        }
+
+        #[test]
+        fn restore_rolls_positions_back_to_the_snapshot() {
+            let mut world = random_world(50, 1.0e7, 77);
+            let snap = world.snapshot();
+            let before: Vec<[f64; 3]> = world.positions.iter().map(|p| [p.x, p.y, p.z]).collect();
+
+            for _ in 0..100 {
+                gravity_system(&mut world, 10.0, MU);
+                propagate_system(&mut world, 10.0);
+            }
+            world.remove_entity(4);
+            assert_ne!(world.positions[0].x, before[0][0]);
+
+            world.restore(&snap);
+            let after: Vec<[f64; 3]> = world.positions.iter().map(|p| [p.x, p.y, p.z]).collect();
+            assert_eq!(after, before);
+            assert_eq!(world.entities_sorted(), (0..50).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn particle_reflects_off_wall() {
+            let mut world = World::new();
+            world.boundary = BoundaryMode::Reflect(10.0);
+            world.add_entity(at(9.0, 0.0, -9.5), moving(2.0, 1.0, -1.0));
+
+            propagate_system(&mut world, 1.0);
+
+            let (pos, vel) = (&world.positions[0], &world.velocities[0]);
+            assert_eq!([pos.x, pos.y, pos.z], [9.0, 1.0, -9.5]);
+            assert_eq!([vel.dx, vel.dy, vel.dz], [-2.0, 1.0, 1.0]);
+        }
+
+        #[test]
+        fn particle_wraps_across_domain() {
+            let mut world = World::new();
+            world.boundary = BoundaryMode::Wrap(10.0);
+            world.add_entity(at(9.0, 0.0, -9.5), moving(2.0, 1.0, -1.0));
+
+            propagate_system(&mut world, 1.0);
+
+            let (pos, vel) = (&world.positions[0], &world.velocities[0]);
+            assert_eq!([pos.x, pos.y, pos.z], [-9.0, 1.0, 9.5]);
+            assert_eq!([vel.dx, vel.dy, vel.dz], [2.0, 1.0, -1.0]);
+        }
+
+        #[test]
+        fn validate_reports_nan_position() {
+            let mut world = random_world(10, 1.0e6, 84);
+            assert_eq!(world.validate(), Ok(()));
+
+            let index = world.index_of(6).unwrap();
+            world.positions[index].y = f64::NAN;
+            assert_eq!(world.validate(), Err(vec![6]));
+        }
+
+        #[test]
+        fn relative_speed_separates_co_moving_from_counter_moving() {
+            let mut world = World::new();
+            world.add_entity(at(7.0e6, 0.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.add_entity(at(7.0e6, 100.0, 0.0), moving(0.0, 7.5e3, 0.0));
+            world.add_entity(at(-7.0e6, 0.0, 0.0), moving(0.0, -7.5e3, 0.0));
+            world.add_entity(at(-7.0e6, 100.0, 0.0), moving(0.0, 7.5e3, 0.0));
+
+            let warnings = proximity_detection_system(&world, 1.0e3);
+
+            assert_eq!(warnings.iter().map(|w| (w.a, w.b)).collect::<Vec<_>>(), vec![(0, 1), (2, 3)]);
+            assert!(warnings[0].relative_speed < 1e-9);
+            assert!((warnings[1].relative_speed - 1.5e4).abs() < 1e-9);
+        }
+
+        #[test]
+        fn query_yields_only_entities_with_position_and_mass() {
+            let mut world = World::new();
+            for k in 0..10 {
+                let id = world.add_entity(at(k as f64, 0.0, 0.0), still());
+                if k % 2 == 0 {
+                    world.insert_component(id, Mass(1.0 + k as f64));
+                }
+            }
+            world.remove_entity(4);
+
+            let ids: Vec<EntityId> = world.query().with::<Position>().with::<Mass>().iter().collect();
+            assert_eq!(ids, vec![0, 2, 6, 8]);
+            assert_eq!(world.query().with::<Position>().iter().count(), 9);
+        }
+
+        #[test]
+        fn entity_below_surface_is_removed() {
+            let mut world = World::new();
+            let sunk = world.add_entity(at(1.0e6, 0.0, 0.0), still());
+            let orbiting = add_circular(&mut world, 7.0e6);
+
+            assert_eq!(surface_collision_system(&mut world, EARTH_EQUATORIAL_RADIUS), vec![sunk]);
+            assert_eq!(world.entities_sorted(), vec![orbiting]);
+        }
+
+        #[test]
+        fn relativistic_correction_advances_perigee() {
+            use std::f64::consts::{PI, TAU};
+
+            // A reduced speed of light scales the advance up to ~0.1 mrad per orbit. The
+            // integrator's own precession cancels in the difference between the two runs.
+            let c = 3.0e6;
+            let (r, e) = (7.0e6, 0.3);
+            let a = r / (1.0 - e);
+            let period = crate::orbit::orbital_period(a, MU);
+            let dt = period / 2000.0;
+            let run = |relativistic: bool| {
+                let mut world = World::new();
+                let v = (MU * (1.0 + e) / r).sqrt();
+                let s = 30f64.to_radians().sin_cos();
+                world.add_entity(at(r, 0.0, 0.0), moving(0.0, v * s.1, v * s.0));
+                for _ in 0..10 * 2000 {
+                    gravity_system(&mut world, dt, MU);
+                    if relativistic {
+                        relativistic_correction_system(&mut world, dt, MU, c);
+                    }
+                    propagate_system(&mut world, dt);
+                }
+                crate::orbit::state_to_elements(&world.positions[0], &world.velocities[0], MU).argp
+            };
+
+            let advance = (run(true) - run(false) + PI).rem_euclid(TAU) - PI;
+            let expected = 10.0 * 6.0 * PI * MU / (c * c * a * (1.0 - e * e));
+            assert!(advance > 0.0);
+            assert!((advance / expected - 1.0).abs() < 0.1, "advance {advance}, expected {expected}");
+        }
+
+        #[test]
+        fn scheduled_thrust_fires_only_inside_its_window() {
+            let mut world = World::new();
+            let id = world.add_entity(at(7.0e6, 0.0, 0.0), still());
+            world.insert_component(id, BurnSchedule {
+                segments: vec![BurnSegment { start: 10.0, end: 20.0, direction: ThrustFrame::Fixed([2.0, 0.0, 0.0]), accel: 0.5 }],
+            });
+
+            let mut kicks = Vec::new();
+            for _ in 0..30 {
+                let (before, epoch) = (world.velocities[0].dx, world.epoch_seconds);
+                scheduled_thrust_system(&mut world, epoch, 1.0);
+                world.advance_time(1.0);
+                kicks.push(world.velocities[0].dx - before);
+            }
+
+            assert_eq!(kicks[..10], [0.0; 10]);
+            assert_eq!(kicks[10..20], [0.5; 10]);
+            assert_eq!(kicks[20..], [0.0; 10]);
+            assert_eq!(world.total_delta_v(id), Some(5.0));
+        }
+
+        #[test]
+        fn limited_warnings_keep_closest_pairs_sorted() {
+            let world = random_world(400, 2.0e5, 100);
+            let all = proximity_detection_grid(&world, 5.0e4);
+            assert!(all.len() > 50);
+
+            let limited = proximity_detection_limited(&world, 5.0e4, 25);
+
+            assert_eq!(limited.len(), 25);
+            assert!(limited.windows(2).all(|w| w[0].distance <= w[1].distance));
+            let mut by_distance = all.clone();
+            by_distance.sort_by(|x, y| x.distance.total_cmp(&y.distance).then((x.a, x.b).cmp(&(y.a, y.b))));
+            assert_eq!(limited, by_distance[..25]);
+            assert_eq!(proximity_detection_limited(&world, 5.0e4, 10_000), by_distance);
+        }
     }
 }
@@ -0,0 +1,270 @@
+// src/maneuver.rs
+
+//! Impulsive maneuvers: instantaneous velocity changes applied to single entities.
+
+use crate::ecs::{EntityId, World};
+use crate::orbit::{stumpff_c, stumpff_s};
+use std::f64::consts::TAU;
+use std::fmt;
+
+/// Bisection steps allowed when solving Lambert's problem.
+const LAMBERT_MAX_ITERATIONS: usize = 200;
+/// Relative time-of-flight tolerance of the Lambert solver.
+const LAMBERT_TOLERANCE: f64 = 1e-12;
+/// Below this sine of the transfer angle the two positions count as collinear.
+const LAMBERT_MIN_SIN_ANGLE: f64 = 1e-8;
+
+/// Adds `dv` (m/s) to the entity's velocity and its magnitude to the entity's
+/// [`DeltaVBudget`](crate::ecs::DeltaVBudget). Returns false if the entity does not exist.
+pub fn apply_impulse(world: &mut World, entity: EntityId, dv: [f64; 3]) -> bool {
+    let Some(i) = world.index_of(entity) else {
+        return false;
+    };
+    let vel = &mut world.velocities[i];
+    vel.dx += dv[0];
+    vel.dy += dv[1];
+    vel.dz += dv[2];
+    world.record_delta_v(entity, (dv[0] * dv[0] + dv[1] * dv[1] + dv[2] * dv[2]).sqrt());
+    true
+}
+
+/// Applies a burn of `magnitude` m/s along the entity's current velocity (negative to retrograde).
+///
+/// Returns false if the entity does not exist or is at rest, since it then has no prograde direction.
+pub fn apply_prograde_burn(world: &mut World, entity: EntityId, magnitude: f64) -> bool {
+    let Some(i) = world.index_of(entity) else {
+        return false;
+    };
+    let vel = &world.velocities[i];
+    let speed = (vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz).sqrt();
+    if speed == 0.0 {
+        return false;
+    }
+    let k = magnitude / speed;
+    apply_impulse(world, entity, [vel.dx * k, vel.dy * k, vel.dz * k])
+}
+
+/// Burns and duration of a [`hohmann_transfer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HohmannResult {
+    /// Magnitude of the departure burn (m/s).
+    pub dv1: f64,
+    /// Magnitude of the arrival burn (m/s).
+    pub dv2: f64,
+    /// Time from the first burn to the second, half the transfer orbit's period (s).
+    pub transfer_time: f64,
+}
+
+/// Computes the two-burn Hohmann transfer between coplanar circular orbits of radii `r1` and `r2`.
+///
+/// Works in either direction; for a descending transfer both burns are retrograde.
+pub fn hohmann_transfer(r1: f64, r2: f64, mu: f64) -> HohmannResult {
+    let a = (r1 + r2) / 2.0;
+    let dv1 = (mu / r1).sqrt() * ((r2 / a).sqrt() - 1.0);
+    let dv2 = (mu / r2).sqrt() * (1.0 - (r1 / a).sqrt());
+    HohmannResult {
+        dv1: dv1.abs(),
+        dv2: dv2.abs(),
+        transfer_time: std::f64::consts::PI * (a * a * a / mu).sqrt(),
+    }
+}
+
+/// Reasons [`lambert`] cannot produce a transfer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LambertError {
+    /// The time of flight is not a finite positive number.
+    InvalidTimeOfFlight,
+    /// The positions are zero, or 0° or 180° apart, so the transfer plane is undefined.
+    DegenerateGeometry,
+    /// The iteration did not reach the requested time of flight.
+    NoConvergence,
+}
+
+impl fmt::Display for LambertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LambertError::InvalidTimeOfFlight => write!(f, "time of flight must be finite and positive"),
+            LambertError::DegenerateGeometry => write!(f, "positions are collinear with the central body"),
+            LambertError::NoConvergence => write!(f, "Lambert iteration did not converge"),
+        }
+    }
+}
+
+impl std::error::Error for LambertError {}
+
+fn norm(a: [f64; 3]) -> f64 {
+    (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt()
+}
+
+/// Solves Lambert's problem: the departure and arrival velocities of the zero-revolution conic
+/// from `r1` to `r2` taking `tof` seconds.
+///
+/// Uses the universal-variable formulation (Bate, Mueller & White), bisecting on z = α χ².
+/// `prograde` picks the transfer whose angular momentum points along +z.
+pub fn lambert(
+    r1: [f64; 3],
+    r2: [f64; 3],
+    tof: f64,
+    mu: f64,
+    prograde: bool,
+) -> Result<([f64; 3], [f64; 3]), LambertError> {
+    if !tof.is_finite() || tof <= 0.0 {
+        return Err(LambertError::InvalidTimeOfFlight);
+    }
+    let (r1n, r2n) = (norm(r1), norm(r2));
+    if r1n == 0.0 || r2n == 0.0 {
+        return Err(LambertError::DegenerateGeometry);
+    }
+    let cross = [
+        r1[1] * r2[2] - r1[2] * r2[1],
+        r1[2] * r2[0] - r1[0] * r2[2],
+        r1[0] * r2[1] - r1[1] * r2[0],
+    ];
+    if norm(cross) < LAMBERT_MIN_SIN_ANGLE * r1n * r2n {
+        return Err(LambertError::DegenerateGeometry);
+    }
+    let cos_dtheta = ((r1[0] * r2[0] + r1[1] * r2[1] + r1[2] * r2[2]) / (r1n * r2n)).clamp(-1.0, 1.0);
+    let mut dtheta = cos_dtheta.acos();
+    if prograde == (cross[2] < 0.0) {
+        dtheta = TAU - dtheta;
+    }
+    let a = dtheta.sin() * (r1n * r2n / (1.0 - cos_dtheta)).sqrt();
+
+    let y = |z: f64| r1n + r2n + a * (z * stumpff_s(z) - 1.0) / stumpff_c(z).sqrt();
+    // Time of flight as a function of z, increasing in z. Where y < 0 no conic exists and the
+    // transfer would need to be even faster, so report -inf to push the search upwards.
+    let time_of_flight = |z: f64| {
+        let y = y(z);
+        if y < 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        let chi = (y / stumpff_c(z)).sqrt();
+        (chi * chi * chi * stumpff_s(z) + a * y.sqrt()) / mu.sqrt()
+    };
+
+    // Bracket the root: z = 4π² is the parabola-free upper limit of a single revolution.
+    let mut hi = TAU * TAU * (1.0 - 1e-9);
+    let mut lo = -TAU * TAU;
+    while time_of_flight(lo) > tof {
+        lo *= 2.0;
+        if lo < -1e8 {
+            return Err(LambertError::NoConvergence);
+        }
+    }
+    if time_of_flight(hi) < tof {
+        return Err(LambertError::NoConvergence);
+    }
+
+    let mut z = 0.5 * (lo + hi);
+    let mut converged = false;
+    for _ in 0..LAMBERT_MAX_ITERATIONS {
+        z = 0.5 * (lo + hi);
+        let t = time_of_flight(z);
+        if (t - tof).abs() <= LAMBERT_TOLERANCE * tof {
+            converged = true;
+            break;
+        }
+        if t < tof {
+            lo = z;
+        } else {
+            hi = z;
+        }
+    }
+    if !converged {
+        return Err(LambertError::NoConvergence);
+    }
+
+    // Lagrange coefficients of the transfer arc.
+    let y = y(z);
+    let f = 1.0 - y / r1n;
+    let g = a * (y / mu).sqrt();
+    let g_dot = 1.0 - y / r2n;
+    let v1 = std::array::from_fn(|k| (r2[k] - f * r1[k]) / g);
+    let v2 = std::array::from_fn(|k| (g_dot * r2[k] - r1[k]) / g);
+    Ok((v1, v2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Position, Velocity, EARTH_EQUATORIAL_RADIUS};
+    use crate::orbit::apogee_perigee;
+
+    const MU: f64 = 3.986004418e14;
+
+    /// A world with one satellite on a circular equatorial orbit of radius `r`.
+    fn circular(r: f64) -> (World, EntityId) {
+        let mut world = World::new();
+        let id = world.add_entity(Position { x: r, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: (MU / r).sqrt(), dz: 0.0 });
+        (world, id)
+    }
+
+    fn apogee(world: &World, id: EntityId) -> f64 {
+        let i = world.index_of(id).unwrap();
+        apogee_perigee(&world.positions[i], &world.velocities[i], MU, EARTH_EQUATORIAL_RADIUS).0.unwrap()
+    }
+
+    #[test]
+    fn prograde_burn_raises_apogee() {
+        let (mut world, id) = circular(7.0e6);
+        let before = apogee(&world, id);
+
+        assert!(apply_prograde_burn(&mut world, id, 100.0));
+
+        assert!(apogee(&world, id) > before + 100.0e3);
+        assert!((world.velocities[0].dy - (MU / 7.0e6).sqrt() - 100.0).abs() < 1e-9);
+        assert!(!apply_prograde_burn(&mut world, id + 1, 100.0));
+    }
+
+    #[test]
+    fn hohmann_leo_to_geo_matches_textbook() {
+        // 300 km LEO to GEO, as in Curtis, Orbital Mechanics for Engineering Students.
+        let transfer = hohmann_transfer(6.678e6, 4.2164e7, MU);
+        assert!((transfer.dv1 - 2.44e3).abs() < 20.0, "dv1 {}", transfer.dv1);
+        assert!((transfer.dv2 - 1.47e3).abs() < 10.0, "dv2 {}", transfer.dv2);
+        assert!((transfer.transfer_time / 3600.0 - 5.28).abs() < 0.01);
+
+        let back = hohmann_transfer(4.2164e7, 6.678e6, MU);
+        assert!((back.dv1 - transfer.dv2).abs() < 1e-9);
+        assert!((back.dv2 - transfer.dv1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lambert_reproduces_known_arc() {
+        use crate::orbit::propagate_kepler;
+        let start = (Position { x: 7.0e6, y: 1.0e6, z: 5.0e5 }, Velocity { dx: -1.0e3, dy: 7.8e3, dz: 1.5e3 });
+        let tof = 2400.0;
+        let (end, arrival) = propagate_kepler(&start.0, &start.1, tof, MU);
+        let r1 = [start.0.x, start.0.y, start.0.z];
+        let r2 = [end.x, end.y, end.z];
+
+        let (v1, v2) = lambert(r1, r2, tof, MU, true).unwrap();
+
+        let departure = [start.1.dx, start.1.dy, start.1.dz];
+        let arrival = [arrival.dx, arrival.dy, arrival.dz];
+        assert!(norm(std::array::from_fn(|k| v1[k] - departure[k])) < 1e-3);
+        assert!(norm(std::array::from_fn(|k| v2[k] - arrival[k])) < 1e-3);
+        let (reached, _) = propagate_kepler(&start.0, &Velocity { dx: v1[0], dy: v1[1], dz: v1[2] }, tof, MU);
+        assert!(norm([reached.x - r2[0], reached.y - r2[1], reached.z - r2[2]]) < 1.0);
+    }
+
+    #[test]
+    fn lambert_rejects_bad_inputs() {
+        let r1 = [7.0e6, 0.0, 0.0];
+        assert_eq!(lambert(r1, [0.0, 7.0e6, 0.0], -1.0, MU, true), Err(LambertError::InvalidTimeOfFlight));
+        assert_eq!(lambert(r1, [-7.0e6, 0.0, 0.0], 3000.0, MU, true), Err(LambertError::DegenerateGeometry));
+    }
+
+    #[test]
+    fn two_impulses_add_up_in_the_budget() {
+        let (mut world, id) = circular(7.0e6);
+        assert_eq!(world.total_delta_v(id), Some(0.0));
+
+        assert!(apply_impulse(&mut world, id, [3.0, 4.0, 0.0]));
+        assert!(apply_impulse(&mut world, id, [0.0, -6.0, 8.0]));
+
+        assert!((world.total_delta_v(id).unwrap() - 15.0).abs() < 1e-12);
+        assert!(!apply_impulse(&mut world, id + 1, [1.0, 0.0, 0.0]));
+        assert_eq!(world.total_delta_v(id + 1), None);
+    }
+}
@@ -0,0 +1,256 @@
+// src/octree.rs
+
+//! A static octree over entity positions for radius and nearest-neighbor queries.
+
+use crate::ecs::{EntityId, Position, World};
+
+/// Most points a node holds before it is split into octants.
+const LEAF_CAPACITY: usize = 8;
+/// Deepest level a node can reach. Starting from a cube spanning the whole constellation
+/// (~1e7 m), cells at this depth are a few meters across, which bounds the tree depth when
+/// many entities share nearly the same position.
+const MAX_DEPTH: usize = 24;
+
+/// A cubic cell covering the points `start..end` of [`Octree::points`].
+#[derive(Debug, Clone)]
+struct Node {
+    center: [f64; 3],
+    half: f64,
+    start: usize,
+    end: usize,
+    /// Indices of the non-empty child cells; empty for leaves.
+    children: Vec<usize>,
+}
+
+/// Octree over the positions of a [`World`] at the moment it was built.
+///
+/// The tree keeps its own copy of the positions, so it does not follow later changes to the
+/// world. Entities with non-finite positions are left out.
+#[derive(Debug, Clone)]
+pub struct Octree {
+    points: Vec<([f64; 3], EntityId)>,
+    nodes: Vec<Node>,
+}
+
+impl Octree {
+    /// Builds an octree over the current positions of every entity in `world`.
+    pub fn build(world: &World) -> Octree {
+        let mut points: Vec<([f64; 3], EntityId)> = world.positions
+            .iter()
+            .zip(world.entities.iter())
+            .map(|(p, &id)| ([p.x, p.y, p.z], id))
+            .filter(|(p, _)| p.iter().all(|x| x.is_finite()))
+            .collect();
+        if points.is_empty() {
+            return Octree { points, nodes: Vec::new() };
+        }
+
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for (p, _) in &points {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+        let center = [
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ];
+        let half = (0..3)
+            .map(|k| (max[k] - min[k]) / 2.0)
+            .fold(1.0, f64::max);
+
+        let mut nodes = Vec::new();
+        let len = points.len();
+        build_node(&mut points, &mut nodes, center, half, 0, len, 0);
+        Octree { points, nodes }
+    }
+
+    /// Returns the ids of the entities within `r` meters of `center`, in ascending order.
+    pub fn query_radius(&self, center: &Position, r: f64) -> Vec<EntityId> {
+        let c = [center.x, center.y, center.z];
+        let r2 = r * r;
+        let mut found = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if box_distance2(node, c) > r2 {
+                continue;
+            }
+            if node.children.is_empty() {
+                found.extend(
+                    self.points[node.start..node.end]
+                        .iter()
+                        .filter(|(p, _)| distance2(*p, c) <= r2)
+                        .map(|&(_, id)| id),
+                );
+            } else {
+                stack.extend(node.children.iter().copied());
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the id of the entity closest to `point`, or `None` if the tree is empty.
+    /// Ties go to the smallest id.
+    pub fn nearest(&self, point: &Position) -> Option<EntityId> {
+        let c = [point.x, point.y, point.z];
+        let mut best: Option<(f64, EntityId)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if best.is_some_and(|(d2, _)| box_distance2(node, c) > d2) {
+                continue;
+            }
+            if node.children.is_empty() {
+                for &(p, id) in &self.points[node.start..node.end] {
+                    let d2 = distance2(p, c);
+                    if best.is_none_or(|(best_d2, best_id)| d2 < best_d2 || (d2 == best_d2 && id < best_id)) {
+                        best = Some((d2, id));
+                    }
+                }
+            } else {
+                // Push the farthest child first so the closest one is searched first and
+                // tightens the bound before the others are visited.
+                let mut children = node.children.clone();
+                children.sort_by(|&a, &b| {
+                    box_distance2(&self.nodes[b], c).total_cmp(&box_distance2(&self.nodes[a], c))
+                });
+                stack.extend(children);
+            }
+        }
+        best.map(|(_, id)| id)
+    }
+}
+
+/// Adds the node covering `points[start..end]` and, recursively, its children, returning its index.
+fn build_node(
+    points: &mut [([f64; 3], EntityId)],
+    nodes: &mut Vec<Node>,
+    center: [f64; 3],
+    half: f64,
+    start: usize,
+    end: usize,
+    depth: usize,
+) -> usize {
+    let index = nodes.len();
+    nodes.push(Node { center, half, start, end, children: Vec::new() });
+    if end - start <= LEAF_CAPACITY || depth == MAX_DEPTH {
+        return index;
+    }
+
+    let octant = |p: &[f64; 3]| {
+        (p[0] >= center[0]) as usize | ((p[1] >= center[1]) as usize) << 1 | ((p[2] >= center[2]) as usize) << 2
+    };
+    points[start..end].sort_by_key(|(p, _)| octant(p));
+
+    let mut children = Vec::new();
+    let mut child_start = start;
+    for code in 0..8 {
+        let child_end = child_start
+            + points[child_start..end]
+                .iter()
+                .take_while(|(p, _)| octant(p) == code)
+                .count();
+        if child_end > child_start {
+            let quarter = half / 2.0;
+            let child_center = [
+                center[0] + if code & 1 != 0 { quarter } else { -quarter },
+                center[1] + if code & 2 != 0 { quarter } else { -quarter },
+                center[2] + if code & 4 != 0 { quarter } else { -quarter },
+            ];
+            children.push(build_node(points, nodes, child_center, quarter, child_start, child_end, depth + 1));
+        }
+        child_start = child_end;
+    }
+    nodes[index].children = children;
+    index
+}
+
+fn distance2(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|k| (a[k] - b[k]) * (a[k] - b[k])).sum()
+}
+
+/// Squared distance from `point` to the nearest point of the node's cell; zero inside it.
+fn box_distance2(node: &Node, point: [f64; 3]) -> f64 {
+    (0..3)
+        .map(|k| {
+            let d = ((point[k] - node.center[k]).abs() - node.half).max(0.0);
+            d * d
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Velocity;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_position(rng: &mut StdRng) -> Position {
+        Position { x: rng.gen_range(-1.0e7..1.0e7), y: rng.gen_range(-1.0e7..1.0e7), z: rng.gen_range(-1.0e7..1.0e7) }
+    }
+
+    fn distance2(a: &Position, b: &Position) -> f64 {
+        (a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)
+    }
+
+    /// 2000 entities spread over the ±1e7 m cube, with a few removed.
+    fn world(rng: &mut StdRng) -> World {
+        let mut world = World::new();
+        for _ in 0..2000 {
+            world.add_entity(random_position(rng), Velocity { dx: 0.0, dy: 0.0, dz: 0.0 });
+        }
+        for id in [0, 17, 1999] {
+            world.remove_entity(id);
+        }
+        world
+    }
+
+    #[test]
+    fn radius_query_returns_exactly_the_entities_in_range() {
+        let mut rng = StdRng::seed_from_u64(76);
+        let world = world(&mut rng);
+        let tree = Octree::build(&world);
+
+        for _ in 0..50 {
+            let center = random_position(&mut rng);
+            let r = rng.gen_range(1.0e5..3.0e6);
+            let expected: Vec<EntityId> = world.entities_sorted()
+                .into_iter()
+                .filter(|&id| distance2(&world.positions[world.index_of(id).unwrap()], &center) <= r * r)
+                .collect();
+            assert_eq!(tree.query_radius(&center, r), expected);
+        }
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(77);
+        let world = world(&mut rng);
+        let tree = Octree::build(&world);
+
+        for _ in 0..200 {
+            let point = random_position(&mut rng);
+            let expected = world.entities_sorted()
+                .into_iter()
+                .min_by(|&a, &b| {
+                    let da = distance2(&world.positions[world.index_of(a).unwrap()], &point);
+                    let db = distance2(&world.positions[world.index_of(b).unwrap()], &point);
+                    da.total_cmp(&db)
+                });
+            assert_eq!(tree.nearest(&point), expected);
+        }
+        assert_eq!(Octree::build(&World::new()).nearest(&Position { x: 0.0, y: 0.0, z: 0.0 }), None);
+    }
+}
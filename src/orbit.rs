@@ -0,0 +1,553 @@
+// src/orbit.rs
+
+//! Two-body orbit math: conversions between Cartesian states and classical orbital elements.
+
+use crate::ecs::{DragProperties, EntityId, Position, Velocity, World};
+use crate::error::{check_mu, check_radius, PhysicsError};
+use std::f64::consts::TAU;
+
+/// Below this eccentricity an orbit is treated as circular.
+const CIRCULAR_TOLERANCE: f64 = 1e-10;
+/// Below this ratio of |n| to |h| an orbit is treated as equatorial.
+const EQUATORIAL_TOLERANCE: f64 = 1e-10;
+/// Convergence tolerance of the Kepler equation solver, in radians.
+const KEPLER_TOLERANCE: f64 = 1e-14;
+/// Iteration cap of the Kepler equation solver.
+const KEPLER_MAX_ITERATIONS: usize = 50;
+
+/// Classical Keplerian orbital elements. Angles are in radians, `a` in meters.
+///
+/// For circular orbits the argument of periapsis is 0 and `nu` holds the argument of latitude;
+/// for equatorial orbits the RAAN is 0 and `argp` holds the longitude of periapsis. A circular
+/// equatorial orbit therefore reports its true longitude in `nu`.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct OrbitalElements {
+    /// Semi-major axis (negative for hyperbolic orbits).
+    pub a: f64,
+    /// Eccentricity.
+    pub e: f64,
+    /// Inclination in [0, π].
+    pub i: f64,
+    /// Right ascension of the ascending node in [0, 2π).
+    pub raan: f64,
+    /// Argument of periapsis in [0, 2π).
+    pub argp: f64,
+    /// True anomaly in [0, 2π).
+    pub nu: f64,
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// Angle between two vectors in [0, π].
+fn angle_between(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (dot(a, b) / (norm(a) * norm(b))).clamp(-1.0, 1.0).acos()
+}
+
+/// [`elements_to_state`] that first rejects a non-positive `mu` or elements whose semi-latus
+/// rectum a (1 - e²) is not positive, such as a negative `a` with e < 1.
+pub fn elements_to_state_checked(elem: &OrbitalElements, mu: f64) -> Result<(Position, Velocity), PhysicsError> {
+    check_mu(mu)?;
+    check_radius(elem.a * (1.0 - elem.e * elem.e))?;
+    Ok(elements_to_state(elem, mu))
+}
+
+/// Stumpff function C(z) = (1 - cos √z) / z, continued to negative `z` with cosh.
+pub(crate) fn stumpff_c(z: f64) -> f64 {
+    if z > 1e-8 {
+        // 2 sin²(√z / 2) avoids the cancellation in 1 - cos √z as z approaches 4π².
+        let s = (z.sqrt() / 2.0).sin();
+        2.0 * s * s / z
+    } else if z < -1e-8 {
+        ((-z).sqrt().cosh() - 1.0) / -z
+    } else {
+        1.0 / 2.0 - z / 24.0
+    }
+}
+
+/// Stumpff function S(z) = (√z - sin √z) / √z³, continued to negative `z` with sinh.
+pub(crate) fn stumpff_s(z: f64) -> f64 {
+    if z > 1e-8 {
+        let sz = z.sqrt();
+        (sz - sz.sin()) / (sz * sz * sz)
+    } else if z < -1e-8 {
+        let sz = (-z).sqrt();
+        (sz.sinh() - sz) / (sz * sz * sz)
+    } else {
+        1.0 / 6.0 - z / 120.0
+    }
+}
+
+/// Computes the classical orbital elements of a Cartesian state around a body with parameter `mu`.
+pub fn state_to_elements(pos: &Position, vel: &Velocity, mu: f64) -> OrbitalElements {
+    let r = [pos.x, pos.y, pos.z];
+    let v = [vel.dx, vel.dy, vel.dz];
+    let r_mag = norm(r);
+    let v_mag = norm(v);
+
+    let h = cross(r, v);
+    let h_mag = norm(h);
+    // Node vector k̂ × h, pointing at the ascending node.
+    let n = [-h[1], h[0], 0.0];
+    let n_mag = norm(n);
+
+    // Eccentricity vector e = ((v² - μ/r) r - (r·v) v) / μ, pointing at periapsis.
+    let rv = dot(r, v);
+    let e_vec = [
+        ((v_mag * v_mag - mu / r_mag) * r[0] - rv * v[0]) / mu,
+        ((v_mag * v_mag - mu / r_mag) * r[1] - rv * v[1]) / mu,
+        ((v_mag * v_mag - mu / r_mag) * r[2] - rv * v[2]) / mu,
+    ];
+    let e = norm(e_vec);
+
+    let energy = v_mag * v_mag / 2.0 - mu / r_mag;
+    let a = -mu / (2.0 * energy);
+    let i = (h[2] / h_mag).clamp(-1.0, 1.0).acos();
+
+    let circular = e < CIRCULAR_TOLERANCE;
+    let equatorial = n_mag < EQUATORIAL_TOLERANCE * h_mag;
+    let retrograde = h[2] < 0.0;
+
+    let raan = if equatorial {
+        0.0
+    } else {
+        let raan = n[1].atan2(n[0]);
+        raan.rem_euclid(TAU)
+    };
+
+    let argp = if circular {
+        0.0
+    } else if equatorial {
+        // Longitude of periapsis, measured in the direction of motion.
+        let lon = e_vec[1].atan2(e_vec[0]);
+        if retrograde { -lon } else { lon }.rem_euclid(TAU)
+    } else {
+        let argp = angle_between(n, e_vec);
+        if e_vec[2] < 0.0 { TAU - argp } else { argp }
+    };
+
+    let nu = if !circular {
+        let nu = angle_between(e_vec, r);
+        if rv < 0.0 { TAU - nu } else { nu }
+    } else if !equatorial {
+        // Argument of latitude.
+        let u = angle_between(n, r);
+        if r[2] < 0.0 { TAU - u } else { u }
+    } else {
+        // True longitude.
+        let lon = r[1].atan2(r[0]);
+        if retrograde { -lon } else { lon }.rem_euclid(TAU)
+    };
+
+    OrbitalElements { a, e, i, raan, argp, nu }
+}
+
+/// [`state_to_elements`] that first rejects a non-positive `mu` or a position at the origin.
+pub fn state_to_elements_checked(pos: &Position, vel: &Velocity, mu: f64) -> Result<OrbitalElements, PhysicsError> {
+    check_mu(mu)?;
+    check_radius(norm([pos.x, pos.y, pos.z]))?;
+    Ok(state_to_elements(pos, vel, mu))
+}
+
+/// Computes the Cartesian state for a set of orbital elements around a body with parameter `mu`.
+///
+/// Uses the same conventions as [`state_to_elements`] for circular and equatorial orbits,
+/// so the two functions round-trip. Parabolic orbits (e = 1) are not representable.
+pub fn elements_to_state(elem: &OrbitalElements, mu: f64) -> (Position, Velocity) {
+    let p = elem.a * (1.0 - elem.e * elem.e);
+    let (sin_nu, cos_nu) = elem.nu.sin_cos();
+
+    // State in the perifocal frame (x̂ towards periapsis, ẑ along h).
+    let r_pf = p / (1.0 + elem.e * cos_nu);
+    let r = [r_pf * cos_nu, r_pf * sin_nu];
+    let sqrt_mu_p = (mu / p).sqrt();
+    let v = [-sqrt_mu_p * sin_nu, sqrt_mu_p * (elem.e + cos_nu)];
+
+    // Perifocal to inertial: R3(-Ω) · R1(-i) · R3(-ω), keeping the two columns we need.
+    let (sin_o, cos_o) = elem.raan.sin_cos();
+    let (sin_i, cos_i) = elem.i.sin_cos();
+    let (sin_w, cos_w) = elem.argp.sin_cos();
+    let p_hat = [
+        cos_o * cos_w - sin_o * sin_w * cos_i,
+        sin_o * cos_w + cos_o * sin_w * cos_i,
+        sin_w * sin_i,
+    ];
+    let q_hat = [
+        -cos_o * sin_w - sin_o * cos_w * cos_i,
+        -sin_o * sin_w + cos_o * cos_w * cos_i,
+        cos_w * sin_i,
+    ];
+
+    let position = Position {
+        x: r[0] * p_hat[0] + r[1] * q_hat[0],
+        y: r[0] * p_hat[1] + r[1] * q_hat[1],
+        z: r[0] * p_hat[2] + r[1] * q_hat[2],
+    };
+    let velocity = Velocity {
+        dx: v[0] * p_hat[0] + v[1] * q_hat[0],
+        dy: v[0] * p_hat[1] + v[1] * q_hat[1],
+        dz: v[0] * p_hat[2] + v[1] * q_hat[2],
+    };
+    (position, velocity)
+}
+
+/// Specific orbital energy v²/2 - μ/r in J/kg. Negative for bound orbits.
+pub fn specific_orbital_energy(pos: &Position, vel: &Velocity, mu: f64) -> f64 {
+    let r = norm([pos.x, pos.y, pos.z]);
+    let v = norm([vel.dx, vel.dy, vel.dz]);
+    v * v / 2.0 - mu / r
+}
+
+/// Specific angular momentum h = r × v in m²/s.
+pub fn specific_angular_momentum(pos: &Position, vel: &Velocity) -> [f64; 3] {
+    cross([pos.x, pos.y, pos.z], [vel.dx, vel.dy, vel.dz])
+}
+
+/// Returns the specific orbital energy and angular momentum of every entity, ordered by id.
+///
+/// Both are conserved under pure two-body motion, so their drift over a run measures integrator error.
+pub fn orbital_invariants(world: &World, mu: f64) -> Vec<(EntityId, f64, [f64; 3])> {
+    world.entities_sorted()
+        .into_iter()
+        .map(|id| {
+            let i = world.index_of(id).expect("sorted ids are live");
+            let (pos, vel) = (&world.positions[i], &world.velocities[i]);
+            (id, specific_orbital_energy(pos, vel, mu), specific_angular_momentum(pos, vel))
+        })
+        .collect()
+}
+
+/// Period (s) of an elliptical orbit with semi-major axis `a`: T = 2π √(a³ / μ).
+pub fn orbital_period(a: f64, mu: f64) -> f64 {
+    TAU / mean_motion(a, mu)
+}
+
+/// Mean motion (rad/s) of an elliptical orbit with semi-major axis `a`: n = √(μ / a³).
+pub fn mean_motion(a: f64, mu: f64) -> f64 {
+    (mu / (a * a * a)).sqrt()
+}
+
+/// Solves Kepler's equation M = E - e sin E for the eccentric anomaly E in [0, 2π), with
+/// Newton-Raphson. Valid for elliptical orbits (0 ≤ e < 1).
+pub fn mean_to_eccentric(m: f64, e: f64) -> f64 {
+    let m = m.rem_euclid(TAU);
+    // Starting at π for high eccentricities keeps Newton from overshooting near periapsis.
+    let mut ecc = if e < 0.8 { m } else { std::f64::consts::PI };
+    for _ in 0..KEPLER_MAX_ITERATIONS {
+        let delta = (ecc - e * ecc.sin() - m) / (1.0 - e * ecc.cos());
+        ecc -= delta;
+        if delta.abs() < KEPLER_TOLERANCE {
+            break;
+        }
+    }
+    ecc.rem_euclid(TAU)
+}
+
+/// Converts an eccentric anomaly to the true anomaly in [0, 2π).
+pub fn eccentric_to_true(ecc: f64, e: f64) -> f64 {
+    let (sin, cos) = (ecc / 2.0).sin_cos();
+    (2.0 * ((1.0 + e).sqrt() * sin).atan2((1.0 - e).sqrt() * cos)).rem_euclid(TAU)
+}
+
+/// Converts a true anomaly to the mean anomaly in [0, 2π).
+pub fn true_to_mean(nu: f64, e: f64) -> f64 {
+    let (sin, cos) = (nu / 2.0).sin_cos();
+    let ecc = 2.0 * ((1.0 - e).sqrt() * sin).atan2((1.0 + e).sqrt() * cos);
+    (ecc - e * ecc.sin()).rem_euclid(TAU)
+}
+
+/// Convergence tolerance of the universal anomaly iteration, relative to the anomaly.
+const UNIVERSAL_TOLERANCE: f64 = 1e-13;
+/// Iteration cap of the universal anomaly iteration.
+const UNIVERSAL_MAX_ITERATIONS: usize = 100;
+
+/// Propagates a state by `dt` seconds along its two-body conic, using the universal-variable
+/// formulation so elliptical, parabolic and hyperbolic orbits are handled alike.
+///
+/// Unlike the numerical systems this is exact up to rounding for any `dt`, positive or negative.
+pub fn propagate_kepler(pos: &Position, vel: &Velocity, dt: f64, mu: f64) -> (Position, Velocity) {
+    let r0 = [pos.x, pos.y, pos.z];
+    let v0 = [vel.dx, vel.dy, vel.dz];
+    let r0_mag = norm(r0);
+    let v0_mag = norm(v0);
+    let sqrt_mu = mu.sqrt();
+    let vr0 = dot(r0, v0) / r0_mag;
+    // Reciprocal of the semi-major axis; zero for a parabola, negative for a hyperbola.
+    let alpha = 2.0 / r0_mag - v0_mag * v0_mag / mu;
+
+    // Solve the universal Kepler equation for χ with Newton's method.
+    let mut chi = sqrt_mu * alpha.abs() * dt;
+    for _ in 0..UNIVERSAL_MAX_ITERATIONS {
+        let z = alpha * chi * chi;
+        let (c, s) = (stumpff_c(z), stumpff_s(z));
+        let f = r0_mag * vr0 / sqrt_mu * chi * chi * c
+            + (1.0 - alpha * r0_mag) * chi * chi * chi * s
+            + r0_mag * chi
+            - sqrt_mu * dt;
+        let df = r0_mag * vr0 / sqrt_mu * chi * (1.0 - z * s)
+            + (1.0 - alpha * r0_mag) * chi * chi * c
+            + r0_mag;
+        let delta = f / df;
+        chi -= delta;
+        if delta.abs() <= UNIVERSAL_TOLERANCE * chi.abs().max(1.0) {
+            break;
+        }
+    }
+
+    // Lagrange coefficients.
+    let z = alpha * chi * chi;
+    let (c, s) = (stumpff_c(z), stumpff_s(z));
+    let f = 1.0 - chi * chi / r0_mag * c;
+    let g = dt - chi * chi * chi * s / sqrt_mu;
+    let r = [
+        f * r0[0] + g * v0[0],
+        f * r0[1] + g * v0[1],
+        f * r0[2] + g * v0[2],
+    ];
+    let r_mag = norm(r);
+    let f_dot = sqrt_mu / (r_mag * r0_mag) * (z * s - 1.0) * chi;
+    let g_dot = 1.0 - chi * chi / r_mag * c;
+
+    let position = Position { x: r[0], y: r[1], z: r[2] };
+    let velocity = Velocity {
+        dx: f_dot * r0[0] + g_dot * v0[0],
+        dy: f_dot * r0[1] + g_dot * v0[1],
+        dz: f_dot * r0[2] + g_dot * v0[2],
+    };
+    (position, velocity)
+}
+
+/// Apogee and perigee altitudes (m) above a spherical body of radius `earth_radius`.
+///
+/// The apogee is `None` for escape trajectories (e ≥ 1), which never come back.
+pub fn apogee_perigee(pos: &Position, vel: &Velocity, mu: f64, earth_radius: f64) -> (Option<f64>, f64) {
+    let elements = state_to_elements(pos, vel, mu);
+    let e = elements.e;
+    // r_p = p / (1 + e) with p = a (1 - e²) stays valid for hyperbolas, where a < 0.
+    let p = elements.a * (1.0 - e * e);
+    let perigee = p / (1.0 + e) - earth_radius;
+    let apogee = (e < 1.0).then_some(elements.a * (1.0 + e) - earth_radius);
+    (apogee, perigee)
+}
+
+/// Orbit-averaged rate of change of the semi-major axis (m/s) caused by drag in air of density
+/// `rho` (kg/m³). Negative, since drag always lowers the orbit.
+///
+/// Uses the near-circular result da/dt = -ρ (Cd A / m) √(μ a), with `a` from the vis-viva
+/// equation, so it is only a rough guide for eccentric orbits.
+pub fn decay_rate(pos: &Position, vel: &Velocity, drag: &DragProperties, mu: f64, rho: f64) -> f64 {
+    let a = -mu / (2.0 * specific_orbital_energy(pos, vel, mu));
+    -rho * (drag.cd * drag.area / drag.mass) * (mu * a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f64 = 3.986004418e14;
+
+    /// Whether two angles agree to within `tol` radians, modulo 2π.
+    fn angle_close(a: f64, b: f64, tol: f64) -> bool {
+        let d = (a - b).rem_euclid(TAU);
+        d.min(TAU - d) < tol
+    }
+
+    #[test]
+    fn elements_of_circular_equatorial_orbit() {
+        let pos = Position { x: 0.0, y: 7.0e6, z: 0.0 };
+        let vel = Velocity { dx: -(MU / 7.0e6).sqrt(), dy: 0.0, dz: 0.0 };
+        let elem = state_to_elements(&pos, &vel, MU);
+
+        assert!((elem.a / 7.0e6 - 1.0).abs() < 1e-12);
+        assert!(elem.e < 1e-12);
+        assert!(elem.i.abs() < 1e-12);
+        assert_eq!((elem.raan, elem.argp), (0.0, 0.0));
+        // True longitude of a point on the +y axis.
+        assert!(angle_close(elem.nu, TAU / 4.0, 1e-12));
+    }
+
+    #[test]
+    fn elements_of_inclined_elliptical_orbit() {
+        // Periapsis of an a = 10 000 km, e = 0.2 orbit, placed on the ascending node at
+        // RAAN 90° with 30° inclination.
+        let (a, e) = (1.0e7, 0.2);
+        let r_p = a * (1.0 - e);
+        let v_p = (MU * (1.0 + e) / r_p).sqrt();
+        let (sin_i, cos_i) = 30f64.to_radians().sin_cos();
+        let pos = Position { x: 0.0, y: r_p, z: 0.0 };
+        let vel = Velocity { dx: -v_p * cos_i, dy: 0.0, dz: v_p * sin_i };
+        let elem = state_to_elements(&pos, &vel, MU);
+
+        assert!((elem.a / a - 1.0).abs() < 1e-12);
+        assert!((elem.e - e).abs() < 1e-12);
+        assert!((elem.i - 30f64.to_radians()).abs() < 1e-12);
+        assert!(angle_close(elem.raan, TAU / 4.0, 1e-12));
+        assert!(angle_close(elem.argp, 0.0, 1e-9));
+        assert!(angle_close(elem.nu, 0.0, 1e-9));
+    }
+
+    #[test]
+    fn state_round_trips_through_elements() {
+        let states = [
+            ([7.0e6, 0.0, 0.0], [0.0, 7.6e3, 0.0]),
+            ([6.8e6, 1.2e6, -3.0e5], [-1.1e3, 7.2e3, 2.5e3]),
+            ([-2.0e7, 3.0e7, 1.0e7], [-2.0e3, -1.5e3, 1.0e3]),
+            ([4.0e6, -5.0e6, 2.0e6], [5.0e3, 4.0e3, -6.0e3]),
+            ([7.0e6, 0.0, 0.0], [0.0, -7.0e3, 1.0e-3]),
+        ];
+        for (r, v) in states {
+            let pos = Position { x: r[0], y: r[1], z: r[2] };
+            let vel = Velocity { dx: v[0], dy: v[1], dz: v[2] };
+            let (p, w) = elements_to_state(&state_to_elements(&pos, &vel, MU), MU);
+
+            let dr = norm([p.x - pos.x, p.y - pos.y, p.z - pos.z]);
+            let dv = norm([w.dx - vel.dx, w.dy - vel.dy, w.dz - vel.dz]);
+            assert!(dr < 1e-6 * norm(r), "{r:?}: position off by {dr} m");
+            assert!(dv < 1e-6 * norm(v), "{r:?}: velocity off by {dv} m/s");
+        }
+    }
+
+    /// Largest relative deviation of the specific energy from its initial value over ten
+    /// periods of a 7000 km circular orbit, advancing with `step` in 10 s steps.
+    fn energy_band(step: impl Fn(&mut World)) -> f64 {
+        let mut world = World::new();
+        world.add_entity(Position { x: 7.0e6, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: (MU / 7.0e6).sqrt(), dz: 0.0 });
+        let initial = orbital_invariants(&world, MU)[0].1;
+        let steps = (10.0 * orbital_period(7.0e6, MU) / 10.0) as usize;
+        (0..steps)
+            .map(|_| {
+                step(&mut world);
+                (orbital_invariants(&world, MU)[0].1 / initial - 1.0).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn leapfrog_energy_stays_in_tighter_band_than_euler() {
+        use crate::ecs::{gravity_system, leapfrog_step, propagate_system};
+        let leapfrog = energy_band(|world| leapfrog_step(world, 10.0, MU));
+        let euler = energy_band(|world| {
+            gravity_system(world, 10.0, MU);
+            propagate_system(world, 10.0);
+        });
+        assert!(leapfrog < 1e-6, "leapfrog {leapfrog}");
+        assert!(euler > 100.0 * leapfrog, "euler {euler}, leapfrog {leapfrog}");
+    }
+
+    #[test]
+    fn invariants_are_listed_by_id() {
+        let mut world = World::new();
+        for r in [7.0e6, 8.0e6, 9.0e6] {
+            world.add_entity(Position { x: r, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: (MU / r).sqrt(), dz: 0.0 });
+        }
+        world.remove_entity(0);
+
+        let invariants = orbital_invariants(&world, MU);
+        assert_eq!(invariants.iter().map(|i| i.0).collect::<Vec<_>>(), vec![1, 2]);
+        // Circular orbits have ε = -μ / 2r and h = √(μ r) along z.
+        assert!((invariants[0].1 + MU / 1.6e7).abs() < 1e-6);
+        assert!((invariants[1].2[2] - (MU * 9.0e6).sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn leo_and_geo_periods() {
+        // 400 km LEO, roughly the ISS.
+        let leo = orbital_period(6.778e6, MU) / 60.0;
+        assert!((leo - 92.6).abs() < 0.1, "LEO {leo} min");
+
+        // GEO matches a sidereal day, 23 h 56 min 4 s.
+        let geo = orbital_period(4.2164e7, MU);
+        assert!((geo - 86164.1).abs() < 5.0, "GEO {geo} s");
+        assert!((mean_motion(4.2164e7, MU) * geo - TAU).abs() < 1e-12);
+    }
+
+    #[test]
+    fn anomalies_round_trip_up_to_high_eccentricity() {
+        for e in [0.0, 0.1, 0.5, 0.8, 0.9, 0.95] {
+            for k in 0..36 {
+                let m = k as f64 * TAU / 36.0;
+                let ecc = mean_to_eccentric(m, e);
+                assert!(angle_close(ecc - e * ecc.sin(), m, 1e-10));
+                let nu = eccentric_to_true(ecc, e);
+                assert!(angle_close(true_to_mean(nu, e), m, 1e-10), "e {e}, M {m}");
+            }
+        }
+    }
+
+    #[test]
+    fn kepler_propagation_returns_after_one_period() {
+        let elements = OrbitalElements { a: 1.2e7, e: 0.4, i: 0.9, raan: 2.0, argp: 1.0, nu: 0.7 };
+        let (pos, vel) = elements_to_state(&elements, MU);
+
+        let (p, v) = propagate_kepler(&pos, &vel, orbital_period(elements.a, MU), MU);
+
+        let r = norm([pos.x, pos.y, pos.z]);
+        let speed = norm([vel.dx, vel.dy, vel.dz]);
+        assert!(norm([p.x - pos.x, p.y - pos.y, p.z - pos.z]) < 1e-6 * r);
+        assert!(norm([v.dx - vel.dx, v.dy - vel.dy, v.dz - vel.dz]) < 1e-6 * speed);
+    }
+
+    #[test]
+    fn kepler_propagation_handles_hyperbolas_both_ways() {
+        let pos = Position { x: 7.0e6, y: 0.0, z: 0.0 };
+        let vel = Velocity { dx: 0.0, dy: 1.2e4, dz: 0.0 };
+
+        let (p, v) = propagate_kepler(&pos, &vel, 3600.0, MU);
+        let (back, back_vel) = propagate_kepler(&p, &v, -3600.0, MU);
+
+        assert!((specific_orbital_energy(&p, &v, MU) / specific_orbital_energy(&pos, &vel, MU) - 1.0).abs() < 1e-9);
+        assert!(norm([back.x - pos.x, back.y - pos.y, back.z - pos.z]) < 1e-3);
+        assert!(norm([back_vel.dx - vel.dx, back_vel.dy - vel.dy, back_vel.dz - vel.dz]) < 1e-6);
+    }
+
+    #[test]
+    fn apogee_and_perigee_of_known_ellipse() {
+        let earth_radius = 6.378137e6;
+        // 500 km by 2000 km.
+        let (r_p, r_a) = (earth_radius + 5.0e5, earth_radius + 2.0e6);
+        let elements = OrbitalElements { a: (r_p + r_a) / 2.0, e: (r_a - r_p) / (r_a + r_p), i: 0.4, raan: 1.0, argp: 2.0, nu: 1.0 };
+        let (pos, vel) = elements_to_state(&elements, MU);
+
+        let (apogee, perigee) = apogee_perigee(&pos, &vel, MU, earth_radius);
+        assert!((apogee.unwrap() - 2.0e6).abs() < 1e-3);
+        assert!((perigee - 5.0e5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn escape_trajectory_has_no_apogee() {
+        let pos = Position { x: 7.0e6, y: 0.0, z: 0.0 };
+        let vel = Velocity { dx: 0.0, dy: 1.2e4, dz: 0.0 };
+        let (apogee, perigee) = apogee_perigee(&pos, &vel, MU, 6.378137e6);
+        assert_eq!(apogee, None);
+        // Moving tangentially, the satellite is at perigee.
+        assert!((perigee - (7.0e6 - 6.378137e6)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn decay_rate_is_negative_and_linear_in_density() {
+        let drag = DragProperties { cd: 2.2, area: 10.0, mass: 500.0 };
+        let r = 6.778e6;
+        let pos = Position { x: r, y: 0.0, z: 0.0 };
+        let vel = Velocity { dx: 0.0, dy: (MU / r).sqrt(), dz: 0.0 };
+
+        let slow = decay_rate(&pos, &vel, &drag, MU, 1.0e-12);
+        let fast = decay_rate(&pos, &vel, &drag, MU, 3.0e-12);
+        assert!(slow < 0.0);
+        assert!((fast / slow - 3.0).abs() < 1e-12);
+        // -ρ (Cd A / m) √(μ a) for a circular orbit of radius r.
+        assert!((slow + 1.0e-12 * 2.2 * 10.0 / 500.0 * (MU * r).sqrt()).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,402 @@
+// src/simulator.rs
+
+//! Headless fixed-step driver for running a world and sampling its trajectory, and a builder
+//! for setting up the world and its parameters in one place.
+
+use crate::constellation::{spawn_random_constellation, SpawnConfig};
+use crate::ecs::{
+    central_acceleration, gravity_system, leapfrog_central, leapfrog_step, propagate_system, rk4_central,
+    rk4_step, Position, World,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::ops::Range;
+
+/// Positions of every entity after `step` steps, in storage order.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub step: usize,
+    pub positions: Vec<Position>,
+}
+
+/// Stepping scheme used by [`Simulator::run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// [`gravity_system`] followed by [`propagate_system`]. Honors gravity bodies and
+    /// [`CentralBody`](crate::ecs::CentralBody) components.
+    #[default]
+    Euler,
+    /// [`rk4_step`] under the central gravity of `mu` alone.
+    Rk4,
+    /// [`leapfrog_step`] under the central gravity of `mu` alone.
+    Leapfrog,
+}
+
+/// Advances every `[x, y, z, dx, dy, dz]` state in `states` by `dt` seconds under the central
+/// gravity of `mu`, without building a [`World`].
+///
+/// Gives the same result as one step of [`Simulator::run`] with the same integrator on a world
+/// holding these states and no gravity bodies or central-body overrides.
+pub fn propagate_states(states: &mut [[f64; 6]], dt: f64, mu: f64, integrator: Integrator) {
+    let step = |state: &mut [f64; 6]| {
+        let [x, y, z, dx, dy, dz] = *state;
+        let (r, v) = match integrator {
+            Integrator::Euler => {
+                let a = central_acceleration([x, y, z], mu);
+                let v = [dx + a[0] * dt, dy + a[1] * dt, dz + a[2] * dt];
+                ([x + v[0] * dt, y + v[1] * dt, z + v[2] * dt], v)
+            }
+            Integrator::Rk4 => rk4_central([x, y, z], [dx, dy, dz], dt, mu),
+            Integrator::Leapfrog => leapfrog_central([x, y, z], [dx, dy, dz], dt, mu),
+        };
+        *state = [r[0], r[1], r[2], v[0], v[1], v[2]];
+    };
+    #[cfg(feature = "rayon")]
+    states.par_iter_mut().for_each(step);
+    #[cfg(not(feature = "rayon"))]
+    states.iter_mut().for_each(step);
+}
+
+/// Runs the chosen [`Integrator`] with a fixed time step and records a [`Snapshot`] every
+/// `steps_between_samples` steps.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    /// Time step in seconds.
+    pub dt: f64,
+    /// Gravitational parameter passed to [`gravity_system`] (m³/s²).
+    pub mu: f64,
+    /// Steps between recorded snapshots. 0 records nothing.
+    pub steps_between_samples: usize,
+    /// Scheme used to advance the world each step.
+    pub integrator: Integrator,
+}
+
+impl Simulator {
+    /// Advances `world` by `total_steps` steps and returns the sampled trajectory.
+    ///
+    /// A snapshot is taken after every `steps_between_samples`-th step, so the result holds
+    /// `total_steps / steps_between_samples` snapshots and never includes the initial state.
+    pub fn run(&self, world: &mut World, total_steps: usize) -> Vec<Snapshot> {
+        let mut snapshots = Vec::new();
+        for step in 1..=total_steps {
+            match self.integrator {
+                Integrator::Euler => {
+                    gravity_system(world, self.dt, self.mu);
+                    propagate_system(world, self.dt);
+                }
+                Integrator::Rk4 => rk4_step(world, self.dt, self.mu),
+                Integrator::Leapfrog => leapfrog_step(world, self.dt, self.mu),
+            }
+            world.advance_time(self.dt);
+            if self.steps_between_samples > 0 && step % self.steps_between_samples == 0 {
+                snapshots.push(Snapshot { step, positions: world.positions.clone() });
+            }
+        }
+        snapshots
+    }
+}
+
+/// Advances every world in `worlds` by one gravity + propagate step of `dt` seconds, stepping
+/// the worlds in parallel.
+///
+/// Meant for constellations sharded into independent sub-worlds (e.g. by altitude band):
+/// entities in different worlds never interact, so the result is the same as stepping the
+/// worlds one after another. With the `rayon` feature the worlds go through rayon's pool;
+/// without it they are split into one batch per available core, each on its own scoped thread,
+/// so the thread count does not grow with the number of worlds.
+pub fn run_partitioned(worlds: &mut [World], dt: f64, mu: f64) {
+    let step = move |world: &mut World| {
+        gravity_system(world, dt, mu);
+        propagate_system(world, dt);
+        world.advance_time(dt);
+    };
+    #[cfg(feature = "rayon")]
+    worlds.par_iter_mut().for_each(step);
+    #[cfg(not(feature = "rayon"))]
+    {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let batch = worlds.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            for batch in worlds.chunks_mut(batch) {
+                scope.spawn(move || batch.iter_mut().for_each(step));
+            }
+        });
+    }
+}
+
+/// Simulation parameters produced by [`WorldBuilder::build`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimConfig {
+    /// Gravitational parameter of the central body (m³/s²).
+    pub gravitational_parameter: f64,
+    /// Time step in seconds.
+    pub dt: f64,
+    /// Distance in meters below which satellites are flagged as too close.
+    pub proximity_threshold: f64,
+}
+
+/// Chainable setup for a [`World`] and its [`SimConfig`].
+///
+/// ```
+/// use hylaean_path::simulator::WorldBuilder;
+///
+/// let (world, config) = WorldBuilder::new()
+///     .timestep(5.0)
+///     .with_random_constellation(50, 42)
+///     .build();
+/// assert_eq!(world.positions.len(), 50);
+/// assert_eq!(config.dt, 5.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorldBuilder {
+    config: SimConfig,
+    radius_range: Range<f64>,
+    eccentricity_range: Range<f64>,
+    constellation: Option<(usize, u64)>,
+}
+
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldBuilder {
+    /// Starts from Earth's gravitational parameter, a 10 s step, a 10 km proximity threshold
+    /// and no satellites.
+    pub fn new() -> Self {
+        Self {
+            config: SimConfig {
+                gravitational_parameter: 3.986004418e14,
+                dt: 10.0,
+                proximity_threshold: 10_000.0,
+            },
+            radius_range: 6.5e6..7.0e6,
+            eccentricity_range: 0.0..0.4,
+            constellation: None,
+        }
+    }
+
+    /// Sets the gravitational parameter of the central body (m³/s²).
+    pub fn gravitational_parameter(mut self, mu: f64) -> Self {
+        self.config.gravitational_parameter = mu;
+        self
+    }
+
+    /// Sets the time step in seconds.
+    pub fn timestep(mut self, dt: f64) -> Self {
+        self.config.dt = dt;
+        self
+    }
+
+    /// Sets the proximity warning distance in meters.
+    pub fn proximity_threshold(mut self, threshold: f64) -> Self {
+        self.config.proximity_threshold = threshold;
+        self
+    }
+
+    /// Sets the radius and eccentricity ranges used by [`WorldBuilder::with_random_constellation`].
+    pub fn orbit_ranges(mut self, radius_range: Range<f64>, eccentricity_range: Range<f64>) -> Self {
+        self.radius_range = radius_range;
+        self.eccentricity_range = eccentricity_range;
+        self
+    }
+
+    /// Spawns `n` random satellites from `seed` when the world is built.
+    pub fn with_random_constellation(mut self, n: usize, seed: u64) -> Self {
+        self.constellation = Some((n, seed));
+        self
+    }
+
+    /// Builds the world and returns it with its configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the gravitational parameter, time step or proximity threshold is not a finite
+    /// positive number, or if an orbit range is empty or outside [0, ∞) for radii and [0, 1)
+    /// for eccentricities.
+    pub fn build(self) -> (World, SimConfig) {
+        let config = self.config;
+        for (name, value) in [
+            ("gravitational parameter", config.gravitational_parameter),
+            ("time step", config.dt),
+            ("proximity threshold", config.proximity_threshold),
+        ] {
+            assert!(value.is_finite() && value > 0.0, "{name} must be a finite positive number, got {value}");
+        }
+        assert!(
+            self.radius_range.start > 0.0 && self.radius_range.start < self.radius_range.end,
+            "radius range must be non-empty and positive, got {:?}",
+            self.radius_range
+        );
+        assert!(
+            self.eccentricity_range.start >= 0.0
+                && self.eccentricity_range.start < self.eccentricity_range.end
+                && self.eccentricity_range.end <= 1.0,
+            "eccentricity range must be a non-empty part of [0, 1), got {:?}",
+            self.eccentricity_range
+        );
+
+        let mut world = World::new();
+        if let Some((n, seed)) = self.constellation {
+            let spawn = SpawnConfig {
+                radius_range: self.radius_range,
+                eccentricity_range: self.eccentricity_range,
+                mu: config.gravitational_parameter,
+            };
+            spawn_random_constellation(&mut world, n, &mut StdRng::seed_from_u64(seed), &spawn);
+        }
+        (world, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Velocity;
+
+    const MU: f64 = 3.986004418e14;
+
+    fn seeded_world(n: usize, seed: u64) -> World {
+        WorldBuilder::new().with_random_constellation(n, seed).build().0
+    }
+
+    #[test]
+    fn run_takes_one_snapshot_per_interval() {
+        let simulator = Simulator { dt: 10.0, mu: MU, steps_between_samples: 7, integrator: Integrator::Euler };
+        let mut world = seeded_world(5, 42);
+
+        let snapshots = simulator.run(&mut world, 100);
+
+        assert_eq!(snapshots.len(), 100 / 7);
+        assert_eq!(snapshots.iter().map(|s| s.step).collect::<Vec<_>>(), (1..=14).map(|k| 7 * k).collect::<Vec<_>>());
+        assert!(snapshots.iter().all(|s| s.positions.len() == 5));
+        assert_eq!(world.epoch_seconds, 1000.0);
+    }
+
+    #[test]
+    fn builder_stores_config_and_spawns_satellites() {
+        let (world, config) = WorldBuilder::new()
+            .gravitational_parameter(4.9048695e12)
+            .timestep(2.5)
+            .proximity_threshold(500.0)
+            .orbit_ranges(2.0e6..2.5e6, 0.0..0.1)
+            .with_random_constellation(50, 66)
+            .build();
+
+        assert_eq!(world.positions.len(), 50);
+        assert_eq!(config, SimConfig { gravitational_parameter: 4.9048695e12, dt: 2.5, proximity_threshold: 500.0 });
+        for pos in &world.positions {
+            let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+            assert!((2.0e6..2.5e6).contains(&r));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "time step must be a finite positive number")]
+    fn builder_rejects_non_positive_timestep() {
+        WorldBuilder::new().timestep(0.0).build();
+    }
+
+    /// Specific orbital energy of the only entity in `world`.
+    fn energy(world: &World) -> f64 {
+        let (p, v) = (&world.positions[0], &world.velocities[0]);
+        let r = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+        0.5 * (v.dx * v.dx + v.dy * v.dy + v.dz * v.dz) - MU / r
+    }
+
+    /// Largest relative energy error within each of `periods` periods of an orbit with e ≈ 0.44,
+    /// stepped 200 times per period.
+    fn energy_errors(integrator: Integrator, periods: usize) -> Vec<f64> {
+        let r = 7.0e6;
+        let mut world = World::new();
+        world.add_entity(Position { x: r, y: 0.0, z: 0.0 }, Velocity { dx: 0.0, dy: 1.2 * (MU / r).sqrt(), dz: 0.0 });
+        let e0 = energy(&world);
+        let period = crate::orbit::orbital_period(-MU / (2.0 * e0), MU);
+        let simulator = Simulator { dt: period / 200.0, mu: MU, steps_between_samples: 0, integrator };
+        (0..periods)
+            .map(|_| {
+                (0..200)
+                    .map(|_| {
+                        simulator.run(&mut world, 1);
+                        ((energy(&world) - e0) / e0).abs()
+                    })
+                    .fold(0.0, f64::max)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leapfrog_energy_drifts_least() {
+        // Drift is the growth of the per-period peak error over 100 periods. RK4 starts out more
+        // accurate, but its error grows every orbit while the symplectic schemes only oscillate.
+        let drift = |integrator| {
+            let errors = energy_errors(integrator, 100);
+            (errors[99] - errors[0]).abs()
+        };
+        let (euler, rk4, leapfrog) = (drift(Integrator::Euler), drift(Integrator::Rk4), drift(Integrator::Leapfrog));
+        assert!(leapfrog < euler / 100.0, "leapfrog {leapfrog:e}, euler {euler:e}");
+        assert!(leapfrog < rk4 / 100.0, "leapfrog {leapfrog:e}, rk4 {rk4:e}");
+    }
+
+    #[test]
+    fn partitioned_run_matches_serial_steps() {
+        let mut worlds: Vec<World> = (0..3).map(|k| seeded_world(40, 83 + k)).collect();
+        let mut serial: Vec<World> = (0..3).map(|k| seeded_world(40, 83 + k)).collect();
+
+        for _ in 0..10 {
+            run_partitioned(&mut worlds, 10.0, MU);
+            for world in serial.iter_mut() {
+                gravity_system(world, 10.0, MU);
+                propagate_system(world, 10.0);
+                world.advance_time(10.0);
+            }
+        }
+
+        for (world, expected) in worlds.iter().zip(&serial) {
+            let state = |w: &World| w.positions.iter().map(|p| [p.x, p.y, p.z]).collect::<Vec<_>>();
+            assert_eq!(state(world), state(expected));
+            assert_eq!(world.epoch_seconds, 100.0);
+        }
+    }
+
+    #[test]
+    fn propagate_states_matches_world_path() {
+        for integrator in [Integrator::Euler, Integrator::Rk4, Integrator::Leapfrog] {
+            let mut world = seeded_world(30, 96);
+            let mut states: Vec<[f64; 6]> = world.iter_mobile()
+                .map(|(_, p, v)| [p.x, p.y, p.z, v.dx, v.dy, v.dz])
+                .collect();
+
+            let simulator = Simulator { dt: 10.0, mu: MU, steps_between_samples: 0, integrator };
+            simulator.run(&mut world, 50);
+            for _ in 0..50 {
+                propagate_states(&mut states, 10.0, MU, integrator);
+            }
+
+            let expected: Vec<[f64; 6]> = world.iter_mobile()
+                .map(|(_, p, v)| [p.x, p.y, p.z, v.dx, v.dy, v.dz])
+                .collect();
+            assert_eq!(states, expected, "{integrator:?}");
+        }
+    }
+
+    #[test]
+    fn partitioned_run_batches_many_worlds() {
+        let mut worlds: Vec<World> = (0..200).map(|k| seeded_world(3, k)).collect();
+        let mut serial: Vec<World> = (0..200).map(|k| seeded_world(3, k)).collect();
+
+        run_partitioned(&mut worlds, 10.0, MU);
+        for world in serial.iter_mut() {
+            gravity_system(world, 10.0, MU);
+            propagate_system(world, 10.0);
+            world.advance_time(10.0);
+        }
+
+        let state = |w: &World| w.positions.iter().map(|p| [p.x, p.y, p.z]).collect::<Vec<_>>();
+        assert!(worlds.iter().zip(&serial).all(|(w, s)| state(w) == state(s) && w.epoch_seconds == 10.0));
+        run_partitioned(&mut [], 10.0, MU);
+    }
+}
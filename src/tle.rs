@@ -0,0 +1,198 @@
+// src/tle.rs
+
+//! Two-Line Element set parsing and conversion to a Cartesian state at epoch.
+
+use crate::ecs::{Position, Velocity};
+use crate::orbit::{eccentric_to_true, elements_to_state, mean_to_eccentric, OrbitalElements};
+use std::f64::consts::TAU;
+use std::fmt;
+
+/// Seconds in a day, for converting mean motion from revolutions per day.
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Mean elements parsed from a Two-Line Element set. Angles are in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tle {
+    /// NORAD catalog number.
+    pub satellite_number: u32,
+    /// Four-digit epoch year.
+    pub epoch_year: u32,
+    /// Epoch day of year, with fractional part (1.0 is midnight on January 1).
+    pub epoch_day: f64,
+    pub inclination: f64,
+    pub raan: f64,
+    pub eccentricity: f64,
+    pub argp: f64,
+    pub mean_anomaly: f64,
+    /// Mean motion in radians per second.
+    pub mean_motion: f64,
+}
+
+/// Reasons a pair of lines is not a valid TLE.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TleError {
+    /// The given line (1 or 2) is shorter than 69 characters or not ASCII.
+    BadLength(u8),
+    /// The given line does not start with its line number.
+    BadLineNumber(u8),
+    /// The checksum in column 69 of the given line does not match its contents.
+    Checksum { line: u8, expected: u32, found: u32 },
+    /// The two lines carry different catalog numbers.
+    SatelliteMismatch,
+    /// A field could not be parsed as a number.
+    BadField(&'static str),
+}
+
+impl fmt::Display for TleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TleError::BadLength(line) => write!(f, "line {line} must be at least 69 ASCII characters"),
+            TleError::BadLineNumber(line) => write!(f, "line {line} does not start with '{line} '"),
+            TleError::Checksum { line, expected, found } => {
+                write!(f, "line {line} checksum is {found}, expected {expected}")
+            }
+            TleError::SatelliteMismatch => write!(f, "lines 1 and 2 have different catalog numbers"),
+            TleError::BadField(name) => write!(f, "invalid {name} field"),
+        }
+    }
+}
+
+impl std::error::Error for TleError {}
+
+/// Modulo-10 checksum over the first 68 columns: digits count their value, '-' counts 1.
+fn checksum(line: &str) -> u32 {
+    line[..68]
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/// Checks the length, line number and checksum of one line, returning it without trailing whitespace.
+fn validate_line(line: &str, number: u8) -> Result<&str, TleError> {
+    let line = line.trim_end();
+    if line.len() < 69 || !line.is_ascii() {
+        return Err(TleError::BadLength(number));
+    }
+    let bytes = line.as_bytes();
+    if bytes[0] != b'0' + number || bytes[1] != b' ' {
+        return Err(TleError::BadLineNumber(number));
+    }
+    let found = (bytes[68] as char)
+        .to_digit(10)
+        .ok_or(TleError::BadField("checksum"))?;
+    let expected = checksum(line);
+    if found != expected {
+        return Err(TleError::Checksum { line: number, expected, found });
+    }
+    Ok(line)
+}
+
+/// Parses the 1-based inclusive column range `start..=end` of a line.
+fn field<T: std::str::FromStr>(line: &str, start: usize, end: usize, name: &'static str) -> Result<T, TleError> {
+    line[start - 1..end]
+        .trim()
+        .parse()
+        .map_err(|_| TleError::BadField(name))
+}
+
+fn angle(line: &str, start: usize, end: usize, name: &'static str) -> Result<f64, TleError> {
+    field::<f64>(line, start, end, name).map(f64::to_radians)
+}
+
+/// Parses a Two-Line Element set, validating both checksums.
+pub fn parse_tle(line1: &str, line2: &str) -> Result<Tle, TleError> {
+    let line1 = validate_line(line1, 1)?;
+    let line2 = validate_line(line2, 2)?;
+
+    let satellite_number = field(line1, 3, 7, "satellite number")?;
+    if field::<u32>(line2, 3, 7, "satellite number")? != satellite_number {
+        return Err(TleError::SatelliteMismatch);
+    }
+
+    // Two-digit years 57-99 are 1957-1999, 00-56 are 2000-2056.
+    let year: u32 = field(line1, 19, 20, "epoch year")?;
+    let epoch_year = if year < 57 { 2000 + year } else { 1900 + year };
+    let epoch_day = field(line1, 21, 32, "epoch day")?;
+
+    // Eccentricity is written with an assumed leading decimal point.
+    let eccentricity = format!("0.{}", line2[26..33].trim())
+        .parse()
+        .map_err(|_| TleError::BadField("eccentricity"))?;
+    let revs_per_day: f64 = field(line2, 53, 63, "mean motion")?;
+
+    Ok(Tle {
+        satellite_number,
+        epoch_year,
+        epoch_day,
+        inclination: angle(line2, 9, 16, "inclination")?,
+        raan: angle(line2, 18, 25, "RAAN")?,
+        eccentricity,
+        argp: angle(line2, 35, 42, "argument of perigee")?,
+        mean_anomaly: angle(line2, 44, 51, "mean anomaly")?,
+        mean_motion: revs_per_day * TAU / SECONDS_PER_DAY,
+    })
+}
+
+/// Converts the mean elements of a TLE to a Cartesian state at its epoch.
+///
+/// The elements are treated as osculating two-body elements around a body with parameter `mu`:
+/// the semi-major axis comes from the mean motion and the true anomaly from Kepler's equation.
+/// This skips SGP4's perturbation terms, so the state differs from an SGP4 state at epoch by
+/// a few kilometers in low Earth orbit.
+pub fn tle_to_state(tle: &Tle, mu: f64) -> (Position, Velocity) {
+    let a = (mu / (tle.mean_motion * tle.mean_motion)).cbrt();
+    let e = tle.eccentricity;
+    let nu = eccentric_to_true(mean_to_eccentric(tle.mean_anomaly, e), e);
+
+    let elements = OrbitalElements {
+        a,
+        e,
+        i: tle.inclination,
+        raan: tle.raan,
+        argp: tle.argp,
+        nu,
+    };
+    elements_to_state(&elements, mu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The ISS on 2008-09-20, the example TLE from the Wikipedia article on the format.
+    const ISS_LINE1: &str = "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927";
+    const ISS_LINE2: &str = "2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    #[test]
+    fn parses_iss_tle() {
+        let tle = parse_tle(ISS_LINE1, ISS_LINE2).unwrap();
+
+        assert_eq!(tle.satellite_number, 25544);
+        assert_eq!((tle.epoch_year, tle.epoch_day), (2008, 264.51782528));
+        assert!((tle.inclination.to_degrees() - 51.6416).abs() < 1e-12);
+        assert!((tle.eccentricity - 0.0006703).abs() < 1e-15);
+        assert!((tle.mean_motion * SECONDS_PER_DAY / TAU - 15.72125391).abs() < 1e-9);
+    }
+
+    #[test]
+    fn iss_state_is_in_low_earth_orbit() {
+        let (pos, vel) = tle_to_state(&parse_tle(ISS_LINE1, ISS_LINE2).unwrap(), 3.986004418e14);
+        let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+        let v = (vel.dx * vel.dx + vel.dy * vel.dy + vel.dz * vel.dz).sqrt();
+        assert!((6.6e6..6.8e6).contains(&r), "radius {r} m");
+        assert!((7.6e3..7.8e3).contains(&v), "speed {v} m/s");
+    }
+
+    #[test]
+    fn rejects_corrupted_checksum() {
+        let corrupted = ISS_LINE2.replace("51.6416", "51.6417");
+        assert_eq!(
+            parse_tle(ISS_LINE1, &corrupted),
+            Err(TleError::Checksum { line: 2, expected: 8, found: 7 })
+        );
+    }
+}
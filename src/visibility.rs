@@ -0,0 +1,167 @@
+// src/visibility.rs
+
+//! Line-of-sight checks against the central body, ground station visibility and sensor
+//! coverage footprints.
+
+use crate::ecs::{EntityId, Position, World};
+use crate::frames::{eci_to_ecef, geodetic_to_ecef, Geodetic};
+
+/// Returns whether the straight segment from `p1` to `p2` clears a sphere of radius
+/// `earth_radius` centered on the origin.
+///
+/// Finds the point of the segment closest to the origin and compares its distance with the
+/// radius; a segment that only grazes the surface still counts as visible.
+pub fn has_line_of_sight(p1: &Position, p2: &Position, earth_radius: f64) -> bool {
+    let d = [p2.x - p1.x, p2.y - p1.y, p2.z - p1.z];
+    let d2 = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+    // Parameter of the closest point along p1 + t d, clamped to the segment.
+    let t = if d2 == 0.0 {
+        0.0
+    } else {
+        (-(p1.x * d[0] + p1.y * d[1] + p1.z * d[2]) / d2).clamp(0.0, 1.0)
+    };
+    let c = [p1.x + t * d[0], p1.y + t * d[1], p1.z + t * d[2]];
+    c[0] * c[0] + c[1] * c[1] + c[2] * c[2] >= earth_radius * earth_radius
+}
+
+/// A ground station that can see satellites above `min_elevation` (radians) over its horizon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundStation {
+    pub geodetic: Geodetic,
+    pub min_elevation: f64,
+}
+
+/// Returns every satellite the station can see at sidereal time `gmst`, with its elevation
+/// angle in radians, ordered by id.
+///
+/// The elevation is measured from the plane perpendicular to the ellipsoid normal at the station.
+pub fn visible_satellites(world: &World, station: &GroundStation, gmst: f64) -> Vec<(EntityId, f64)> {
+    let site = geodetic_to_ecef(&station.geodetic);
+    let (sin_lat, cos_lat) = station.geodetic.lat.sin_cos();
+    let (sin_lon, cos_lon) = station.geodetic.lon.sin_cos();
+    let up = [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat];
+
+    world.entities_sorted()
+        .into_iter()
+        .filter_map(|id| {
+            let pos = &world.positions[world.index_of(id)?];
+            let sat = eci_to_ecef(pos, gmst);
+            let rho = [sat.x - site.x, sat.y - site.y, sat.z - site.z];
+            let range = (rho[0] * rho[0] + rho[1] * rho[1] + rho[2] * rho[2]).sqrt();
+            if range == 0.0 {
+                return None;
+            }
+            let sin_el = (rho[0] * up[0] + rho[1] * up[1] + rho[2] * up[2]) / range;
+            let elevation = sin_el.clamp(-1.0, 1.0).asin();
+            (elevation >= station.min_elevation).then_some((id, elevation))
+        })
+        .collect()
+}
+
+/// Radius (m, measured along the surface) of the ground circle seen by a nadir-pointing sensor
+/// with cone half-angle `half_angle` (radians) on a satellite at `pos`, over a spherical body of
+/// radius `earth_radius`.
+///
+/// The cone edge meets the ground at the Earth central angle λ = asin(r sin η / R) - η. A cone
+/// wider than the Earth's disc is cut off by the horizon instead, at λ = acos(R / r). Returns 0
+/// for a satellite at or below the surface.
+pub fn footprint_radius(pos: &Position, earth_radius: f64, half_angle: f64) -> f64 {
+    let r = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
+    if r <= earth_radius || half_angle <= 0.0 {
+        return 0.0;
+    }
+    let sin_edge = r * half_angle.sin() / earth_radius;
+    let central_angle = if half_angle >= std::f64::consts::FRAC_PI_2 || sin_edge >= 1.0 {
+        (earth_radius / r).acos()
+    } else {
+        sin_edge.asin() - half_angle
+    };
+    earth_radius * central_angle
+}
+
+/// Returns whether `station` lies inside the [`footprint_radius`] of a nadir-pointing sensor on
+/// a satellite at inertial position `pos`, at sidereal time `gmst`.
+///
+/// Both are treated as points over a sphere of radius `earth_radius`: the station is covered
+/// when the angle between it and the sub-satellite point, seen from the center, is within the
+/// footprint's central angle.
+pub fn points_covered(station: &Geodetic, pos: &Position, gmst: f64, earth_radius: f64, half_angle: f64) -> bool {
+    let sat = eci_to_ecef(pos, gmst);
+    let r = (sat.x * sat.x + sat.y * sat.y + sat.z * sat.z).sqrt();
+    if r == 0.0 {
+        return false;
+    }
+    let (sin_lat, cos_lat) = station.lat.sin_cos();
+    let (sin_lon, cos_lon) = station.lon.sin_cos();
+    let cos_angle = (cos_lat * cos_lon * sat.x + cos_lat * sin_lon * sat.y + sin_lat * sat.z) / r;
+    cos_angle.clamp(-1.0, 1.0).acos() <= footprint_radius(pos, earth_radius, half_angle) / earth_radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R: f64 = 6.378137e6;
+
+    fn at(x: f64, y: f64, z: f64) -> Position {
+        Position { x, y, z }
+    }
+
+    #[test]
+    fn satellites_on_same_side_see_each_other() {
+        assert!(has_line_of_sight(&at(7.0e6, 0.0, 0.0), &at(7.0e6, 1.0e6, 0.0), R));
+        // The closest point of the segment lies beyond its end, so clamping keeps it visible.
+        assert!(has_line_of_sight(&at(7.0e6, 0.0, 0.0), &at(2.0e7, 0.0, 0.0), R));
+    }
+
+    #[test]
+    fn earth_occludes_satellites_on_opposite_sides() {
+        assert!(!has_line_of_sight(&at(7.0e6, 0.0, 0.0), &at(-7.0e6, 0.0, 0.0), R));
+        assert!(!has_line_of_sight(&at(7.0e6, 1.0e6, 0.0), &at(-7.0e6, 1.0e6, 0.0), R));
+    }
+
+    #[test]
+    fn satellite_overhead_is_at_ninety_degrees() {
+        use crate::ecs::Velocity;
+        use crate::frames::ecef_to_eci;
+
+        let site = Geodetic { lat: 30f64.to_radians(), lon: 45f64.to_radians(), alt: 100.0 };
+        let station = GroundStation { geodetic: site, min_elevation: 10f64.to_radians() };
+        let gmst = 1.0;
+        let overhead = ecef_to_eci(&geodetic_to_ecef(&Geodetic { alt: 5.0e5, ..site }), gmst);
+        let antipode = at(-overhead.x, -overhead.y, -overhead.z);
+        let still = Velocity { dx: 0.0, dy: 0.0, dz: 0.0 };
+
+        let mut world = World::new();
+        world.add_entity(antipode, still.clone());
+        let id = world.add_entity(overhead, still);
+
+        let visible = visible_satellites(&world, &station, gmst);
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].0, id);
+        assert!((visible[0].1.to_degrees() - 90.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn geo_footprint_covers_about_a_third_of_the_globe() {
+        let geo = at(4.2164e7, 0.0, 0.0);
+        // A 10° cone is wider than the Earth's disc seen from GEO (about 8.7°), so the horizon
+        // bounds the footprint.
+        let half_angle = 10f64.to_radians();
+        let lambda = footprint_radius(&geo, R, half_angle) / R;
+        assert!((lambda - (R / 4.2164e7).acos()).abs() < 1e-12);
+
+        // Stations spread evenly over the sphere on a Fibonacci lattice.
+        let n = 20_000;
+        let covered = (0..n)
+            .filter(|&k| {
+                let lat = (1.0 - 2.0 * (k as f64 + 0.5) / n as f64).asin();
+                let lon = k as f64 * std::f64::consts::PI * (3.0 - 5f64.sqrt());
+                points_covered(&Geodetic { lat, lon, alt: 0.0 }, &geo, 0.0, R, half_angle)
+            })
+            .count();
+        let fraction = covered as f64 / n as f64;
+        assert!((fraction - (1.0 - lambda.cos()) / 2.0).abs() < 1e-3);
+        assert!(fraction > 0.33 && fraction < 0.45, "fraction = {fraction}");
+    }
+}
@@ -1,16 +1,30 @@
 // src/wasm_interface.rs
 
+use js_sys::Float64Array;
+use std::cell::{RefCell, RefMut};
 use wasm_bindgen::prelude::*;
-use crate::ecs;
-use crate::ecs::{World, Position, Velocity, gravity_system, propagate_system, proximity_detection_system};
-use rand::Rng;
-use std::f64::consts::TAU;
+use crate::constellation::{spawn_random_constellation, SpawnConfig};
+use crate::ecs::{World, Label, Position, ProximityWarning, Velocity, gravity_system, propagate_system, proximity_detection_system, speed};
+use crate::error::check_mu;
+use crate::orbit::{state_to_elements, OrbitalElements};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
 
 #[wasm_bindgen]
 pub struct Simulation {
     world: World,
     gravitational_parameter: f64,
     dt: f64,
+    proximity_threshold: f64,
+    /// Warnings found by the last `step`, with their distances.
+    proximity_details: Vec<ProximityWarning>,
+    /// Backing memory of the array returned by `get_positions_typed`, reused between calls.
+    positions_buffer: RefCell<Vec<f64>>,
+    /// While set, `step` and `step_many` do nothing.
+    paused: bool,
+    /// Factor applied to `dt` by every step.
+    time_scale: f64,
 }
 
 #[wasm_bindgen]
@@ -18,124 +32,621 @@ impl Simulation {
     /// Creates a new simulation with `n_satellites` randomly generated.
     #[wasm_bindgen(constructor)]
     pub fn new(n_satellites: usize) -> Simulation {
+        Simulation::new_seeded(n_satellites, rand::random())
+    }
+
+    /// Creates a new simulation whose `n_satellites` are generated from `seed`,
+    /// so the same seed always gives the same layout.
+    #[wasm_bindgen]
+    pub fn new_seeded(n_satellites: usize, seed: u64) -> Simulation {
         let gravitational_parameter = 3.986004418e14; // Earth's gravitational parameter (m³/s²)
         let dt = 10.0; // time step in seconds
-        let mut world = World::new();
-        let mut rng = rand::thread_rng();
-
-        // Create n random satellites in full 3D space.
-        for _ in 0..n_satellites {
-            // Generate a random orbital radius between 6.5e6 and 7.0e6 meters.
-            let r: f64 = rng.gen_range(7.6e6..7.601e6);
-
-            // Random azimuth angle (θ) in [0, 2π)
-            let theta = rng.gen_range(0.0..TAU);
-            // Random cosine of inclination (u) in [-1, 1]
-            let u: f64 = rng.gen_range(-1.0..1.0);
-            // Inclination φ = acos(u); sin(φ) = sqrt(1 - u²)
-            // let phi = u.acos();
-            let sin_phi = (1.0 - u * u).sqrt();
-    
-            // Convert spherical coordinates to Cartesian coordinates.
-            let pos = Position {
-                x: r * sin_phi * theta.cos(),
-                y: r * sin_phi * theta.sin(),
-                z: r * u,
-            };
-    
-            // 1. Pick a small eccentricity (e.g. up to 0.1 for “nearly” circular)
-            let e: f64 = rng.gen_range(0.0..0.001);
-    
-            // 2. Pick a random true anomaly ν in [0, 2π)
-            let nu: f64 = rng.gen_range(0.0..TAU);
-    
-            // 3. Compute the semi‑latus rectum p = r * (1 + e cos ν)
-            let p = r * (1.0 + e * nu.cos());
-    
-            // 4. Compute radial & tangential speeds for an ellipse
-            //    Vᵣ = √(μ/p) · e · sin ν
-            //    Vₜ = √(μ/p) · (1 + e cos ν)
-            let mu = gravitational_parameter;
-            let sqrt_mu_p = (mu / p).sqrt();
-            let vr = sqrt_mu_p * e * nu.sin();
-            let vt = sqrt_mu_p * (1.0 + e * nu.cos());
-    
-            // 5. Unit radial vector r̂ = pos / r
-            let r_hat = (pos.x / r, pos.y / r, pos.z / r);
-    
-            // 6. Pick a random “reference” vector and orthogonalize to r̂ to get the orbital plane normal
-            let n_hat = loop {
-                let candidate = (
-                    rng.gen_range(-1.0..1.0),
-                    rng.gen_range(-1.0..1.0),
-                    rng.gen_range(-1.0..1.0),
-                );
-                // cross(r̂, candidate)
-                let cross = (
-                    r_hat.1 * candidate.2 - r_hat.2 * candidate.1,
-                    r_hat.2 * candidate.0 - r_hat.0 * candidate.2,
-                    r_hat.0 * candidate.1 - r_hat.1 * candidate.0,
-                );
-                let norm = (cross.0*cross.0 + cross.1*cross.1 + cross.2*cross.2).sqrt();
-                if norm > 1e-6 {
-                    break (cross.0 / norm, cross.1 / norm, cross.2 / norm);
-                }
-            };
-    
-            // 7. Tangential unit vector θ̂ = cross(n̂, r̂)
-            let theta_hat = (
-                n_hat.1 * r_hat.2 - n_hat.2 * r_hat.1,
-                n_hat.2 * r_hat.0 - n_hat.0 * r_hat.2,
-                n_hat.0 * r_hat.1 - n_hat.1 * r_hat.0,
-            );
-    
-            // 8. Combine radial + tangential components
-            let vel = Velocity {
-                dx: vr * r_hat.0 + vt * theta_hat.0,
-                dy: vr * r_hat.1 + vt * theta_hat.1,
-                dz: vr * r_hat.2 + vt * theta_hat.2,
-            };
-    
-            world.add_entity(pos, vel);
-        }
-
-        Simulation {
-            world,
+        let proximity_threshold = 200000.0; // proximity warning distance in meters
+        let mut simulation = Simulation {
+            world: World::new(),
             gravitational_parameter,
             dt,
-        }
+            proximity_threshold,
+            proximity_details: Vec::new(),
+            positions_buffer: RefCell::new(Vec::new()),
+            paused: false,
+            time_scale: 1.0,
+        };
+        simulation.spawn(n_satellites, seed);
+        simulation
+    }
+
+    /// Clears the simulation in place and regenerates `n_satellites` from `seed`, reusing the
+    /// existing buffers.
+    ///
+    /// Every id is freed, so as with `remove_satellite` the old ids are handed out again, from 0
+    /// up: drop ids kept from before the reset. The clock restarts at zero, while the time step, gravitational parameter, proximity threshold,
+    /// pause state and time scale are kept. The satellites are put on orbits for the current
+    /// gravitational parameter: with the default one the result is the same as
+    /// `new_seeded(n_satellites, seed)`, otherwise only the positions are.
+    #[wasm_bindgen]
+    pub fn reset(&mut self, n_satellites: usize, seed: u64) {
+        self.world.despawn_all();
+        self.world.epoch_seconds = 0.0;
+        self.proximity_details.clear();
+        self.spawn(n_satellites, seed);
     }
 
     /// Advances the simulation by one time step.
     #[wasm_bindgen]
     pub fn step(&mut self) {
-        gravity_system(&mut self.world, self.dt, self.gravitational_parameter);
-        propagate_system(&mut self.world, self.dt);
-        
-        // Set a reasonable threshold for proximity detection
-        let proximity_threshold = 200000.0;
-        
+        self.step_many(1);
+    }
+
+    /// Advances the simulation by `count` time steps in one call.
+    ///
+    /// Each step covers `dt` times the time scale. Proximity warnings are only recomputed after
+    /// the last step. Does nothing while the simulation is paused.
+    #[wasm_bindgen]
+    pub fn step_many(&mut self, count: usize) {
+        if self.paused {
+            return;
+        }
+        let dt = self.dt * self.time_scale;
+        for _ in 0..count {
+            gravity_system(&mut self.world, dt, self.gravitational_parameter);
+            propagate_system(&mut self.world, dt);
+            self.world.advance_time(dt);
+        }
+
         // Get new warnings
-        let new_warnings = proximity_detection_system(&self.world, proximity_threshold);
-        
+        let new_warnings = proximity_detection_system(&self.world, self.proximity_threshold);
+
         // Clear and update warnings
         self.world.proximity_warnings.clear();
-        self.world.proximity_warnings.extend(new_warnings);
+        self.world
+            .proximity_warnings
+            .extend(new_warnings.iter().flat_map(|w| [w.a, w.b]));
+        self.proximity_details = new_warnings;
+    }
+
+    /// Adds a satellite with the given position (m) and velocity (m/s), returning its id.
+    #[wasm_bindgen]
+    pub fn add_satellite(&mut self, x: f64, y: f64, z: f64, dx: f64, dy: f64, dz: f64) -> usize {
+        self.world.add_entity(Position { x, y, z }, Velocity { dx, dy, dz })
+    }
+
+    /// Removes a satellite, returning whether it existed.
+    ///
+    /// Its id is handed to the next satellite added, so forget the id once it is removed.
+    #[wasm_bindgen]
+    pub fn remove_satellite(&mut self, id: usize) -> bool {
+        self.world.remove_entity(id)
+    }
+
+    /// Attaches a label to a satellite, replacing any previous one.
+    /// Returns false if the satellite does not exist.
+    #[wasm_bindgen]
+    pub fn set_label(&mut self, id: usize, label: String) -> bool {
+        self.world.insert_component(id, Label(label))
+    }
+
+    /// Returns the label of every satellite as a JS array of strings, with `null` for unlabeled
+    /// satellites, in the same order as `get_positions`.
+    #[wasm_bindgen]
+    pub fn get_labels(&self) -> JsValue {
+        to_value_with_nulls(&self.labels())
+    }
+
+    /// Sets the time step in seconds. Throws if `dt` is not a finite positive number.
+    #[wasm_bindgen]
+    pub fn set_dt(&mut self, dt: f64) -> Result<(), JsError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(JsError::new("dt must be a finite positive number"));
+        }
+        self.dt = dt;
+        Ok(())
+    }
+
+    /// Returns the time step in seconds.
+    #[wasm_bindgen]
+    pub fn get_dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// Pauses or resumes the simulation. While paused, `step` and `step_many` do nothing.
+    #[wasm_bindgen]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns whether the simulation is paused.
+    #[wasm_bindgen]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets the factor each step multiplies `dt` by, e.g. 2 to run twice as fast.
+    /// Throws if `scale` is not a finite non-negative number.
+    #[wasm_bindgen]
+    pub fn set_time_scale(&mut self, scale: f64) -> Result<(), JsError> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(JsError::new("time scale must be a finite non-negative number"));
+        }
+        self.time_scale = scale;
+        Ok(())
     }
 
-    /// Returns the positions of all satellites as a JS array of [x, y, z] values.
+    /// Returns the factor each step multiplies `dt` by.
+    #[wasm_bindgen]
+    pub fn get_time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Sets the gravitational parameter of the central body (m³/s²).
+    /// Throws if `mu` is not a finite positive number.
+    #[wasm_bindgen]
+    pub fn set_gravitational_parameter(&mut self, mu: f64) -> Result<(), JsError> {
+        check_mu(mu)?;
+        self.gravitational_parameter = mu;
+        Ok(())
+    }
+
+    /// Returns the gravitational parameter of the central body (m³/s²).
+    #[wasm_bindgen]
+    pub fn get_gravitational_parameter(&self) -> f64 {
+        self.gravitational_parameter
+    }
+
+    /// Sets the distance in meters below which two satellites are flagged by `step`.
+    /// Throws if `t` is not a finite non-negative number.
+    #[wasm_bindgen]
+    pub fn set_proximity_threshold(&mut self, t: f64) -> Result<(), JsError> {
+        if !t.is_finite() || t < 0.0 {
+            return Err(JsError::new("proximity threshold must be a finite non-negative number"));
+        }
+        self.proximity_threshold = t;
+        Ok(())
+    }
+
+    /// Returns the proximity warning distance in meters.
+    #[wasm_bindgen]
+    pub fn get_proximity_threshold(&self) -> f64 {
+        self.proximity_threshold
+    }
+
+    /// Returns the ids of all satellites, in the order every per-satellite getter such as
+    /// `get_positions` lists them.
+    ///
+    /// That order is the order of the underlying storage: removing a satellite moves the last
+    /// one into its place, so after a removal the array index of a satellite is no longer its
+    /// id. Use this to match getter entries with the ids taken by `remove_satellite` and
+    /// `set_label`, or returned by `get_proximity_warnings`.
+    #[wasm_bindgen]
+    pub fn get_ids(&self) -> Vec<usize> {
+        self.world.entities.clone()
+    }
+
+    /// Returns the positions of all satellites as a JS array of [x, y, z] values, in the same
+    /// order as `get_ids`.
     #[wasm_bindgen]
     pub fn get_positions(&self) -> JsValue {
-        let positions: Vec<[f64; 3]> = self.world.positions
+        serde_wasm_bindgen::to_value(&self.positions()).unwrap()
+    }
+
+    /// Returns the positions of all satellites as a flat `Float64Array` of length `3 * count`,
+    /// `[x, y, z, x, y, z, ...]` in the same order as `get_positions`.
+    ///
+    /// The array is a view straight into WebAssembly memory, so nothing is serialized or copied
+    /// on the Rust side. It is only valid until the next call into the simulation: stepping,
+    /// adding satellites or calling this method again may overwrite or move the memory behind it.
+    /// Copy it (e.g. with `slice()`) to keep the values.
+    #[wasm_bindgen]
+    pub fn get_positions_typed(&self) -> Float64Array {
+        let buffer = self.fill_positions_buffer();
+        // SAFETY: the view points into `positions_buffer`, which lives as long as the simulation
+        // and is only written by this method. Nothing in Rust allocates before the view reaches
+        // JS, and the caller is told the view is invalidated by the next call into the simulation.
+        unsafe { Float64Array::view(&buffer) }
+    }
+
+    /// Returns the positions of the satellites inside the axis-aligned box `[min, max]` as a JS
+    /// array of [x, y, z] values, in the same order as `get_positions`.
+    /// Throws if `min` or `max` does not have exactly three values.
+    #[wasm_bindgen]
+    pub fn get_positions_in_box(&self, min: &[f64], max: &[f64]) -> Result<JsValue, JsError> {
+        let (Ok(min), Ok(max)) = (<[f64; 3]>::try_from(min), <[f64; 3]>::try_from(max)) else {
+            return Err(JsError::new("min and max must have length 3"));
+        };
+        Ok(serde_wasm_bindgen::to_value(&self.positions_in_box(min, max)).unwrap())
+    }
+
+    /// Returns the velocities of all satellites as a JS array of [dx, dy, dz] values,
+    /// in the same order as `get_positions`.
+    #[wasm_bindgen]
+    pub fn get_velocities(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.velocities()).unwrap()
+    }
+
+    /// Returns the speed of every satellite in m/s, in the same order as `get_positions`.
+    #[wasm_bindgen]
+    pub fn get_speeds(&self) -> Vec<f64> {
+        self.world.velocities.iter().map(speed).collect()
+    }
+
+    /// Returns the positions and velocities of all satellites as one flat array with a stride
+    /// of 6: `[x, y, z, dx, dy, dz, x, y, z, ...]`, in the same order as `get_positions`.
+    #[wasm_bindgen]
+    pub fn get_state_flat(&self) -> Vec<f64> {
+        self.world.positions
             .iter()
-            .map(|p| [p.x, p.y, p.z])
-            .collect();
-        JsValue::from_serde(&positions).unwrap()
+            .zip(self.world.velocities.iter())
+            .flat_map(|(p, v)| [p.x, p.y, p.z, v.dx, v.dy, v.dz])
+            .collect()
+    }
+
+    /// Returns the orbital elements of all satellites as a JS array of
+    /// `{ a, e, i, raan, argp, nu }` objects, in the same order as `get_positions`.
+    ///
+    /// Satellites whose elements are undefined (e.g. at rest, or on a parabolic orbit) get `null`
+    /// instead of an object containing NaN or infinity.
+    #[wasm_bindgen]
+    pub fn get_elements(&self) -> JsValue {
+        to_value_with_nulls(&self.elements())
     }
 
     /// Returns the IDs of satellites currently in proximity warning state
     #[wasm_bindgen]
     pub fn get_proximity_warnings(&self) -> JsValue {
-        JsValue::from_serde(&self.world.proximity_warnings).unwrap()
+        serde_wasm_bindgen::to_value(&self.world.proximity_warnings).unwrap()
+    }
+
+    /// Returns the warnings found by the last step as a JS array of
+    /// `{ a, b, distance, relative_speed }` objects, one per pair with `a < b`, the distance in
+    /// meters and the relative speed in m/s, ordered by `a` then `b`.
+    #[wasm_bindgen]
+    pub fn get_proximity_details(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.proximity_details).unwrap()
+    }
+}
+
+/// Converts `value` to a JS value, writing `None` as `null` rather than the default `undefined`.
+fn to_value_with_nulls<T: Serialize>(value: &T) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true))
+        .unwrap()
+}
+
+impl Simulation {
+    /// Orbital elements of all satellites in storage order, `None` where they are undefined.
+    fn elements(&self) -> Vec<Option<OrbitalElements>> {
+        self.world.positions
+            .iter()
+            .zip(self.world.velocities.iter())
+            .map(|(p, v)| {
+                let el = state_to_elements(p, v, self.gravitational_parameter);
+                [el.a, el.e, el.i, el.raan, el.argp, el.nu]
+                    .iter()
+                    .all(|x| x.is_finite())
+                    .then_some(el)
+            })
+            .collect()
+    }
+
+    /// Positions of all satellites in storage order.
+    fn positions(&self) -> Vec<[f64; 3]> {
+        self.world.positions.iter().map(|p| [p.x, p.y, p.z]).collect()
+    }
+
+    /// Refills `positions_buffer` with the flattened positions of all satellites and returns it.
+    fn fill_positions_buffer(&self) -> RefMut<'_, Vec<f64>> {
+        let mut buffer = self.positions_buffer.borrow_mut();
+        buffer.clear();
+        buffer.extend(self.world.positions.iter().flat_map(|p| [p.x, p.y, p.z]));
+        buffer
+    }
+
+    /// Positions inside the axis-aligned box `[min, max]`, in storage order.
+    fn positions_in_box(&self, min: [f64; 3], max: [f64; 3]) -> Vec<[f64; 3]> {
+        self.positions()
+            .into_iter()
+            .filter(|p| (0..3).all(|k| min[k] <= p[k] && p[k] <= max[k]))
+            .collect()
+    }
+
+    /// Velocities of all satellites in storage order.
+    fn velocities(&self) -> Vec<[f64; 3]> {
+        self.world.velocities.iter().map(|v| [v.dx, v.dy, v.dz]).collect()
+    }
+
+    /// Labels of all satellites in storage order.
+    fn labels(&self) -> Vec<Option<&str>> {
+        self.world.entities
+            .iter()
+            .map(|&id| self.world.get_component::<Label>(id).map(|l| l.0.as_str()))
+            .collect()
+    }
+
+    /// Creates `n_satellites` random satellites in full 3D space around the central body.
+    fn spawn(&mut self, n_satellites: usize, seed: u64) {
+        let config = SpawnConfig {
+            radius_range: 7.6e6..7.601e6,
+            eccentricity_range: 0.0..0.001,
+            mu: self.gravitational_parameter,
+        };
+        spawn_random_constellation(&mut self.world, n_satellites, &mut StdRng::seed_from_u64(seed), &config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_satellites_show_up_in_getters() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        let a = sim.add_satellite(7.0e6, 0.0, 0.0, 0.0, 7.5e3, 0.0);
+        let b = sim.add_satellite(0.0, 8.0e6, 0.0, -7.0e3, 0.0, 0.0);
+
+        assert_eq!(sim.positions().len(), 2);
+        assert_eq!(sim.get_state_flat().len(), 12);
+        assert_eq!(sim.get_ids(), vec![a, b]);
+    }
+
+    #[test]
+    fn ids_follow_positions_after_removal() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        let ids: Vec<usize> = (0..4).map(|k| sim.add_satellite(k as f64, 0.0, 0.0, 0.0, 0.0, 0.0)).collect();
+
+        assert!(sim.remove_satellite(ids[1]));
+        assert!(!sim.remove_satellite(ids[1]));
+
+        let order = sim.get_ids();
+        assert_eq!(order.len(), 3);
+        for (id, pos) in order.iter().zip(sim.positions()) {
+            assert_eq!(pos[0], *id as f64);
+        }
+    }
+
+    #[test]
+    fn reset_matches_fresh_seeded_simulation() {
+        let mut sim = Simulation::new_seeded(20, 7);
+        sim.step_many(5);
+        sim.add_satellite(1.0e7, 0.0, 0.0, 0.0, 6.0e3, 0.0);
+        sim.remove_satellite(3);
+        sim.set_label(0, "old".to_string());
+        let old = sim.world.entity(0).unwrap();
+
+        sim.reset(30, 99);
+        let fresh = Simulation::new_seeded(30, 99);
+
+        assert_eq!(sim.get_ids(), fresh.get_ids());
+        assert_eq!(sim.get_state_flat(), fresh.get_state_flat());
+        assert_eq!(sim.world.epoch_seconds, 0.0);
+        assert!(sim.labels().iter().all(Option::is_none));
+        assert!(!sim.world.is_alive(old));
+    }
+
+    #[test]
+    fn reset_spawns_orbits_for_current_gravitational_parameter() {
+        let mut sim = Simulation::new_seeded(5, 3);
+        sim.set_gravitational_parameter(4.9e12).unwrap();
+        sim.reset(5, 3);
+        let fresh = Simulation::new_seeded(5, 3);
+
+        assert_eq!(sim.positions(), fresh.positions());
+        assert_ne!(sim.get_state_flat(), fresh.get_state_flat());
+    }
+
+    #[test]
+    fn elements_of_circular_orbit_and_degenerate_state() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        let mu = sim.get_gravitational_parameter();
+        let r = 7.0e6;
+        sim.add_satellite(r, 0.0, 0.0, 0.0, (mu / r).sqrt(), 0.0);
+        sim.add_satellite(r, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+        let elements = sim.elements();
+        let circular = elements[0].expect("circular orbit has elements");
+        assert!(circular.e < 1e-12);
+        assert!((circular.a - r).abs() < 1e-3);
+        assert!(elements[1].is_none());
+    }
+
+    #[test]
+    fn labels_read_back_in_order_after_step() {
+        let mut sim = Simulation::new_seeded(3, 5);
+        let ids = sim.get_ids();
+        assert!(sim.set_label(ids[2], "beta".to_string()));
+        assert!(sim.set_label(ids[0], "alpha".to_string()));
+        assert!(!sim.set_label(1000, "missing".to_string()));
+
+        sim.step();
+
+        assert_eq!(sim.labels(), vec![Some("alpha"), None, Some("beta")]);
+    }
+
+    #[test]
+    fn speeds_follow_getter_order() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        let ids: Vec<usize> = [3.0, 6.0, 9.0]
+            .iter()
+            .map(|&v| sim.add_satellite(7.0e6, 0.0, 0.0, 0.0, v, 4.0 * v / 3.0))
+            .collect();
+        sim.remove_satellite(ids[0]);
+
+        assert_eq!(sim.get_ids(), vec![ids[2], ids[1]]);
+        assert_eq!(sim.get_speeds(), vec![15.0, 10.0]);
+    }
+
+    #[test]
+    fn one_velocity_per_satellite() {
+        let mut sim = Simulation::new_seeded(25, 22);
+        sim.remove_satellite(4);
+        sim.add_satellite(7.0e6, 0.0, 0.0, 1.0, 2.0, 3.0);
+
+        let velocities = sim.velocities();
+        assert_eq!(velocities.len(), 25);
+        assert_eq!(velocities.len(), sim.positions().len());
+        assert_eq!(velocities[24], [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn set_dt_scales_distance_per_step() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        // Too weak to change the velocity at all, so the satellite coasts.
+        sim.set_gravitational_parameter(f64::MIN_POSITIVE).unwrap();
+        sim.add_satellite(0.0, 0.0, 0.0, 100.0, 0.0, 0.0);
+
+        sim.step();
+        assert_eq!(sim.positions()[0][0], 1000.0);
+        assert!(sim.set_dt(25.0).is_ok());
+        assert_eq!(sim.get_dt(), 25.0);
+        sim.step();
+        assert_eq!(sim.positions()[0][0], 3500.0);
+    }
+
+    #[test]
+    fn lower_threshold_reports_fewer_warnings() {
+        let mut wide = Simulation::new_seeded(200, 25);
+        let mut narrow = Simulation::new_seeded(200, 25);
+        narrow.set_proximity_threshold(wide.get_proximity_threshold() / 4.0).unwrap();
+
+        wide.step();
+        narrow.step();
+
+        assert!(!narrow.proximity_details.is_empty());
+        assert!(narrow.proximity_details.len() < wide.proximity_details.len());
+        assert!(narrow.world.proximity_warnings.len() < wide.world.proximity_warnings.len());
+    }
+
+    #[test]
+    fn same_seed_gives_same_first_position() {
+        let first = Simulation::new_seeded(10, 26);
+        let second = Simulation::new_seeded(10, 26);
+        let other = Simulation::new_seeded(10, 27);
+
+        assert_eq!(first.positions()[0], second.positions()[0]);
+        assert_eq!(first.get_state_flat(), second.get_state_flat());
+        assert_ne!(first.positions()[0], other.positions()[0]);
+    }
+
+    #[test]
+    fn state_flat_has_stride_six() {
+        let sim = Simulation::new_seeded(12, 44);
+        let flat = sim.get_state_flat();
+
+        assert_eq!(flat.len(), 6 * 12);
+        let (p, v) = (&sim.world.positions[0], &sim.world.velocities[0]);
+        assert_eq!(flat[..6], [p.x, p.y, p.z, v.dx, v.dy, v.dz]);
+    }
+
+    #[test]
+    fn step_many_matches_repeated_step() {
+        let mut batched = Simulation::new_seeded(40, 46);
+        let mut single = Simulation::new_seeded(40, 46);
+
+        batched.step_many(10);
+        for _ in 0..10 {
+            single.step();
+        }
+
+        assert_eq!(batched.get_state_flat(), single.get_state_flat());
+        assert_eq!(batched.proximity_details, single.proximity_details);
+        assert_eq!(batched.world.epoch_seconds, single.world.epoch_seconds);
+    }
+
+    #[test]
+    fn box_culling_excludes_outside_satellites() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        sim.add_satellite(1.0, 2.0, 3.0, 0.0, 0.0, 0.0);
+        sim.add_satellite(1.0, 20.0, 3.0, 0.0, 0.0, 0.0);
+        sim.add_satellite(-5.0, 0.0, 5.0, 0.0, 0.0, 0.0);
+
+        let inside = sim.positions_in_box([-5.0, -10.0, 0.0], [10.0, 10.0, 5.0]);
+        assert_eq!(inside, vec![[1.0, 2.0, 3.0], [-5.0, 0.0, 5.0]]);
+    }
+
+    #[test]
+    fn proximity_details_carry_finite_positive_distances() {
+        let mut sim = Simulation::new_seeded(0, 1);
+        let a = sim.add_satellite(7.0e6, 0.0, 0.0, 0.0, 7.5e3, 0.0);
+        let b = sim.add_satellite(7.0e6 + 500.0, 0.0, 0.0, 0.0, 7.5e3, 0.0);
+        sim.add_satellite(-7.0e6, 0.0, 0.0, 0.0, -7.5e3, 0.0);
+        sim.set_proximity_threshold(1.0e4).unwrap();
+        sim.step();
+
+        assert_eq!(sim.proximity_details.len(), 1);
+        let warning = &sim.proximity_details[0];
+        assert_eq!((warning.a, warning.b), (a, b));
+        assert!(warning.distance.is_finite() && warning.distance > 0.0);
+        assert!(warning.distance < 1.0e4);
+        assert_eq!(sim.world.proximity_warnings, [a, b].into());
+    }
+
+    #[test]
+    fn typed_positions_hold_three_values_per_satellite() {
+        let mut sim = Simulation::new_seeded(25, 92);
+        assert_eq!(sim.fill_positions_buffer().len(), 3 * 25);
+
+        sim.remove_satellite(4);
+        sim.step();
+        let buffer = sim.fill_positions_buffer().clone();
+        assert_eq!(buffer.len(), 3 * 24);
+        assert_eq!(buffer, sim.positions().concat());
+    }
+
+    #[test]
+    fn paused_step_leaves_positions_unchanged() {
+        let mut sim = Simulation::new_seeded(10, 93);
+        let before = sim.positions();
+
+        sim.set_paused(true);
+        assert!(sim.is_paused());
+        sim.step();
+        sim.step_many(10);
+        assert_eq!(sim.positions(), before);
+        assert_eq!(sim.world.epoch_seconds, 0.0);
+
+        sim.set_paused(false);
+        sim.set_time_scale(2.0).unwrap();
+        sim.step();
+        assert_ne!(sim.positions(), before);
+        assert_eq!(sim.world.epoch_seconds, 2.0 * sim.get_dt());
+    }
+
+    /// Tests that need a JS host, run with `wasm-pack test --node`.
+    #[cfg(target_arch = "wasm32")]
+    mod wasm {
+        use super::*;
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        #[wasm_bindgen_test]
+        fn typed_positions_have_three_values_per_satellite() {
+            let mut sim = Simulation::new_seeded(25, 92);
+            assert_eq!(sim.get_positions_typed().length(), 3 * 25);
+
+            sim.remove_satellite(4);
+            sim.step();
+            let typed = sim.get_positions_typed().to_vec();
+            assert_eq!(typed.len(), 3 * 24);
+            assert_eq!(typed, sim.positions().concat());
+        }
+
+        #[wasm_bindgen_test]
+        fn invalid_gravitational_parameter_is_rejected() {
+            let mut sim = Simulation::new_seeded(0, 1);
+            let mu = sim.get_gravitational_parameter();
+            for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+                assert!(sim.set_gravitational_parameter(bad).is_err());
+            }
+            assert_eq!(sim.get_gravitational_parameter(), mu);
+        }
+
+        #[wasm_bindgen_test]
+        fn invalid_proximity_threshold_is_rejected() {
+            let mut sim = Simulation::new_seeded(0, 1);
+            let threshold = sim.get_proximity_threshold();
+            for bad in [-1.0, f64::NAN, f64::INFINITY] {
+                assert!(sim.set_proximity_threshold(bad).is_err());
+            }
+            assert_eq!(sim.get_proximity_threshold(), threshold);
+            assert!(sim.set_proximity_threshold(0.0).is_ok());
+        }
     }
 }